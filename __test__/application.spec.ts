@@ -0,0 +1,13 @@
+import test from 'ava'
+
+import {Application} from '../index.js'
+
+test('Application: a window event handler is required', (t) => {
+    const message = /one of `onWindowEvent`, `onLazyWindowEvent` or `onEvents` is required/
+    t.throws(() => Application.withSyncRef({onResumed: () => {}}), {message})
+    t.throws(() => Application.withAsyncFx({onResumed: async () => {}}), {message})
+
+    t.notThrows(() => Application.withSyncRef({onResumed: () => {}, onWindowEvent: () => {}}))
+    t.notThrows(() => Application.withSyncRef({onResumed: () => {}, onLazyWindowEvent: () => {}}))
+    t.notThrows(() => Application.withSyncRef({onResumed: () => {}, onEvents: () => {}}))
+})
@@ -0,0 +1,11 @@
+import test from 'ava'
+
+import {DisplayBackend, EventLoop} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('EventLoop.create: backend() reports the display winit connected to', (t) => {
+    const eventLoop = EventLoop.create({appId: 'napi-winit-test'})
+    // winit prefers wayland when both are set
+    t.is(eventLoop.backend(), process.env.WAYLAND_DISPLAY ? DisplayBackend.Wayland : DisplayBackend.X11)
+})
@@ -0,0 +1,30 @@
+import test from 'ava'
+
+import {BackgroundEventLoop, BackgroundEvent, WindowAttributes} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('BackgroundEventLoop: windows on the loop thread', async (t) => {
+    const events: BackgroundEvent[] = []
+    let exited: () => void
+    const exiting = new Promise<void>((resolve) => (exited = resolve))
+
+    const eventLoop = BackgroundEventLoop.spawn({}, (event) => {
+        events.push(event)
+        if (event.type === 'Exiting') exited()
+    })
+
+    const windowId = eventLoop.createWindow(new WindowAttributes().withTitle('background').withVisible(false))
+    eventLoop.setTitle(windowId, 'renamed')
+    eventLoop.present(windowId, new Uint32Array(4 * 3).fill(0xff0000), 4, 3)
+    t.throws(() => eventLoop.present(windowId, new Uint32Array(4), 4, 3), {message: /do not fill a 4x3 frame/})
+    eventLoop.closeWindow(windowId)
+    t.throws(() => eventLoop.present(windowId, new Uint32Array(1), 1, 1), {message: /window is closed/})
+    eventLoop.exit()
+    await exiting
+    eventLoop.join()
+
+    t.false(eventLoop.running)
+    t.is(events[0].type, 'Resumed')
+    t.throws(() => eventLoop.exit(), {message: 'the event loop has exited'})
+})
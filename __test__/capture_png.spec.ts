@@ -0,0 +1,37 @@
+import test from 'ava'
+
+import {Application, EventLoop, Extra, WindowAttributes} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+// runs on Wayland too, where softbuffer can't read the surface back
+;(hasDisplay ? test : test.skip)('BufferSurface: capturePng encodes the last presented frame', (t) => {
+    const eventLoop = new EventLoop()
+    let done = false
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            if (done) return
+            done = true
+            const window = activeLoop.createWindow(new WindowAttributes().withInnerSize({type: 'Physical', width: 4, height: 2}))
+            const surface = new Extra.BufferSurface(window)
+            t.throws(() => surface.capturePng(), {message: 'nothing has been presented yet'})
+
+            const {width, height} = window.innerSize()
+            const pixels = new Uint32Array(width * height).fill(0x336699)
+            surface.presentWithTyped(pixels)
+
+            const image = Extra.Image.decode(surface.capturePng())
+            t.is(image.width, width)
+            t.is(image.height, height)
+            t.deepEqual(Array.from(image.toPixels()), Array.from(pixels))
+            activeLoop.exit()
+        },
+        onWindowEvent: () => {},
+    })
+
+    for (let i = 0; i < 50 && !done; i++) {
+        eventLoop.pumpAppEvents(10, app)
+    }
+    t.true(done)
+})
@@ -0,0 +1,33 @@
+import test from 'ava'
+
+import {EventLoop, Extra} from '../index.js'
+
+// X11 only, under Xvfb: wayland compositors hand out the clipboard to focused windows only
+const hasX11 = process.platform === 'linux' && Boolean(process.env.DISPLAY) && !process.env.WAYLAND_DISPLAY
+
+;(hasX11 ? test : test.skip)('Clipboard: text, html, images and the primary selection', (t) => {
+    const eventLoop = new EventLoop()
+    const clipboard = new Extra.Clipboard(eventLoop.ownedDisplayHandle())
+
+    clipboard.setText('copied')
+    t.is(clipboard.getText(), 'copied')
+
+    clipboard.setText('selected', Extra.ClipboardKind.Primary)
+    t.is(clipboard.getText(Extra.ClipboardKind.Primary), 'selected')
+    t.is(clipboard.getText(), 'copied')
+
+    clipboard.setHtml('<b>bold</b>', 'bold')
+    t.is(clipboard.getHtml(), '<b>bold</b>')
+
+    const rgba = Buffer.from([255, 0, 0, 255, 0, 255, 0, 128])
+    clipboard.setImage({width: 2, height: 1, rgba})
+    const image = clipboard.getImage()
+    t.is(image?.width, 2)
+    t.is(image?.height, 1)
+    t.deepEqual(image?.rgba, rgba)
+
+    t.throws(() => clipboard.setImage({width: 2, height: 2, rgba}), {message: /does not match 2x2/})
+
+    clipboard.clear()
+    t.is(clipboard.getText(), null)
+})
@@ -0,0 +1,28 @@
+import test from 'ava'
+
+import {ModifiersState, WindowButtons} from '../index.js'
+
+test('ModifiersState: set algebra', (t) => {
+    const shortcut = ModifiersState.fromNames(['SHIFT', 'CONTROL'])
+    const pressed = ModifiersState.fromNames(['CONTROL', 'ALT'])
+
+    t.deepEqual(shortcut.union(pressed).toNames(), ['SHIFT', 'CONTROL', 'ALT'])
+    t.deepEqual(shortcut.intersection(pressed).toNames(), ['CONTROL'])
+    t.deepEqual(shortcut.difference(pressed).toNames(), ['SHIFT'])
+    t.true(shortcut.intersects(pressed))
+    t.false(pressed.contains(shortcut))
+    t.true(pressed.union(shortcut).contains(shortcut))
+    t.true(shortcut.equals(ModifiersState.fromNames(['control', 'shift'])))
+})
+
+test('WindowButtons: toggle and toString', (t) => {
+    const buttons = WindowButtons.all().toggle(WindowButtons.fromNames(['MAXIMIZE']))
+
+    t.is(buttons.toString(), 'CLOSE | MINIMIZE')
+    t.is(WindowButtons.empty().toString(), '')
+    t.true(buttons.complement().equals(WindowButtons.fromNames(['MAXIMIZE'])))
+})
+
+test('WindowButtons: unknown name errors', (t) => {
+    t.throws(() => WindowButtons.fromNames(['HELP']), {message: /unknown flag `HELP`/})
+})
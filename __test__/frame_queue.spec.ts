@@ -0,0 +1,44 @@
+import test from 'ava'
+
+import {Extra} from '../index.js'
+
+test('FrameQueue: newer submits drop unpresented frames', (t) => {
+    const queue = new Extra.FrameQueue(4, 2)
+
+    const first = queue.acquire()!
+    t.is(first.view.length, 8)
+    first.view.fill(0xFF0000)
+    queue.submit(first)
+    t.true(queue.hasNewFrame)
+
+    const second = queue.acquire()!
+    queue.submit(second)
+
+    const stats = queue.stats()
+    t.is(stats.submitted, 2)
+    t.is(stats.dropped, 1)
+})
+
+test('FrameQueue: double buffering runs out of buffers', (t) => {
+    const queue = new Extra.FrameQueue(1, 1, {buffers: 2})
+    t.truthy(queue.acquire())
+    t.truthy(queue.acquire())
+    t.is(queue.acquire(), null)
+    t.is(queue.stats().timeouts, 1)
+})
+
+test('FrameQueue: resize makes acquired frames stale', (t) => {
+    const queue = new Extra.FrameQueue(1, 1)
+    const frame = queue.acquire()!
+    queue.resize(2, 2)
+    queue.submit(frame)
+    t.is(queue.stats().stale, 1)
+    t.false(queue.hasNewFrame)
+})
+
+test('FrameQueue: attach by id', (t) => {
+    const queue = new Extra.FrameQueue(1, 1)
+    const attached = Extra.FrameQueue.attach(queue.id)
+    t.is(attached.width, 1)
+    t.throws(() => Extra.FrameQueue.attach(0), {message: /no frame queue/})
+})
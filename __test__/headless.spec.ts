@@ -0,0 +1,12 @@
+import test from 'ava'
+
+import {EventLoop} from '../index.js'
+
+// runs on every linux host, with or without a display: the variables winit picks the backend from are removed
+;(process.platform === 'linux' ? test : test.skip)('EventLoop.create: throws instead of aborting without a display', (t) => {
+    delete process.env.DISPLAY
+    delete process.env.WAYLAND_DISPLAY
+    delete process.env.WAYLAND_SOCKET
+
+    t.throws(() => EventLoop.create(), {message: /neither WAYLAND_DISPLAY nor WAYLAND_SOCKET nor DISPLAY is set/})
+})
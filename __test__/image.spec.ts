@@ -0,0 +1,28 @@
+import test from 'ava'
+
+import {Extra} from '../index.js'
+
+test('Image: png round trip', (t) => {
+    const pixels = new Uint32Array([0xFF0000, 0x00FF00, 0x0000FF, 0xFFFFFF])
+    const png = Extra.encodePng(pixels, 2, 2)
+
+    const image = Extra.Image.decode(png)
+    t.is(image.width, 2)
+    t.is(image.height, 2)
+    t.false(image.hasAlpha)
+    t.deepEqual(Array.from(image.toPixels()), Array.from(pixels))
+})
+
+test('Image: blit with scaling', (t) => {
+    const image = Extra.Image.fromPixels(new Uint32Array([0x111111, 0x222222]), 2, 1)
+    const view = new Uint32Array(4 * 2)
+
+    image.blitTo(view, 4, 2, {width: 4, height: 2, filter: Extra.ImageFilter.Nearest})
+    t.deepEqual(Array.from(view.subarray(0, 4)), [0x111111, 0x111111, 0x222222, 0x222222])
+})
+
+test('Image: size mismatch errors', (t) => {
+    t.throws(() => Extra.encodePng(new Uint32Array(3), 2, 2), {
+        message: /does not match/
+    })
+})
@@ -0,0 +1,32 @@
+import test from 'ava'
+
+import {Application, EventLoop, ImeSession, WindowAttributes} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('ImeSession: an open session does not keep its window alive', async (t) => {
+    const eventLoop = new EventLoop()
+    const events: string[] = []
+    let session: ImeSession | undefined
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            if (session) return
+            const window = activeLoop.createWindow(new WindowAttributes().withVisible(false))
+            session = ImeSession.create(window)
+            t.is(session.preedit, '')
+            t.true(activeLoop.closeWindow(window.id()))
+        },
+        onWindowEvent: (_activeLoop, _windowId, event) => {
+            events.push(event.type)
+        },
+    })
+
+    for (let i = 0; i < 50 && !events.includes('Destroyed'); i++) {
+        eventLoop.pumpAppEvents(10, app)
+        global.gc!()
+        await new Promise((resolve) => setImmediate(resolve))
+    }
+    t.true(events.includes('Destroyed'))
+    t.throws(() => session!.enabled, {message: /ImeSession is closed/})
+})
@@ -0,0 +1,34 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('LazyWindowEvent: event is converted once and hot fields are read directly', (t) => {
+    const eventLoop = new EventLoop()
+    let created = false
+    let checked = false
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            if (created) return
+            created = true
+            activeLoop.createWindow(new WindowAttributes().withVisible(false)).requestRedraw()
+        },
+        onLazyWindowEvent: (_activeLoop, _windowId, lazy) => {
+            if (lazy.type !== 'RedrawRequested') return
+            checked = true
+            t.is(lazy.key, null)
+            t.is(lazy.position, null)
+            t.is(lazy.state, null)
+            const event = lazy.event
+            t.is(event.type, 'RedrawRequested')
+            t.is(lazy.event, event)
+        },
+    })
+
+    for (let i = 0; i < 50 && !checked; i++) {
+        eventLoop.pumpAppEvents(10, app)
+    }
+    t.true(checked)
+})
@@ -0,0 +1,49 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes, type Window} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('runAppOnLibuv: node keeps running while the driver pumps, stop releases the loop', async (t) => {
+    const eventLoop = new EventLoop()
+    let window: Window | undefined
+    let redraws = 0
+
+    t.throws(() => eventLoop.runAppOnLibuv(Application.withSyncFx({onResumed: () => {}, onWindowEvent: () => {}})), {
+        message: /needs an application made with `withSyncRef`/,
+    })
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            window ??= activeLoop.createWindow(new WindowAttributes().withVisible(false))
+        },
+        onWindowEvent: (_activeLoop, _windowId, event) => {
+            if (event.type === 'RedrawRequested') redraws++
+        },
+    })
+    const driver = eventLoop.runAppOnLibuv(app)
+    t.true(driver.running)
+    t.throws(() => eventLoop.pumpAppEvents(0, app), {message: /driven by `runAppOnLibuv`/})
+    t.throws(() => eventLoop.runAppOnLibuv(app), {message: /already driven by libuv/})
+
+    // timers and promises settle between pumps
+    const ticks = await new Promise<number>((resolve) => {
+        let ticks = 0
+        const interval = setInterval(() => {
+            window?.requestRedraw()
+            if (++ticks === 10) {
+                clearInterval(interval)
+                resolve(ticks)
+            }
+        }, 10)
+    })
+    t.is(ticks, 10)
+    t.truthy(window)
+    t.true(redraws > 0)
+
+    driver.stop()
+    t.false(driver.running)
+    t.is(driver.exitCode, null)
+    // the loop is free for js again, and the closed handles no longer keep node alive
+    t.notThrows(() => eventLoop.pumpAppEvents(0, app))
+})
@@ -0,0 +1,47 @@
+import test from 'ava'
+
+import {Extra} from '../index.js'
+
+test('DynamicBuffer: acquire and release', (t) => {
+    const pool = new Extra.DynamicBuffer(2, 16)
+    t.is(pool.capacity, 2)
+
+    const a = pool.acquire()
+    const b = pool.acquire()
+    t.is(a.length, 4)
+    t.true(a.buffer instanceof SharedArrayBuffer)
+    t.is(pool.available, 0)
+
+    pool.release(a)
+    t.is(pool.available, 1)
+    t.throws(() => pool.release(a), {message: /already been released/})
+    t.throws(() => pool.release(new Uint32Array(4)), {message: /does not belong/})
+
+    pool.release(b)
+    t.is(pool.available, 2)
+})
+
+test('DynamicBuffer: views are handed out again after release', (t) => {
+    const pool = Extra.DynamicBuffer.forFrame(2, 2, 1)
+    const a = pool.acquire()
+    a[0] = 0xff00ff
+    pool.release(a)
+
+    const b = pool.acquire()
+    t.is(b.buffer, a.buffer)
+    t.is(b[0], 0xff00ff)
+})
+
+test('DynamicBuffer: grows by ratio', (t) => {
+    const pool = new Extra.DynamicBuffer(2, 16, 2)
+    pool.acquire()
+    pool.acquire()
+    pool.acquire()
+    t.is(pool.capacity, 4)
+})
+
+test('DynamicBuffer: invalid arguments', (t) => {
+    t.throws(() => new Extra.DynamicBuffer(1.5, 16), {message: /count/})
+    t.throws(() => new Extra.DynamicBuffer(1, 6), {message: /multiple of 4/})
+    t.throws(() => new Extra.DynamicBuffer(1, 16, 1), {message: /ratio/})
+})
@@ -0,0 +1,96 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes, type ActiveEventLoop, type Window} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+// one event loop per process, shared by the tests of this file
+const eventLoop = hasDisplay ? new EventLoop() : undefined
+let window: Window | undefined
+
+const onResumed = (activeLoop: ActiveEventLoop) => {
+    window ??= activeLoop.createWindow(new WindowAttributes().withVisible(false))
+}
+
+// Pumps until `count` redraws went through the layers, a redraw is requested before each pump
+function pumpRedraws(app: Application, seen: () => number, count: number) {
+    for (let i = 0; i < 50 && seen() < count; i++) {
+        window?.requestRedraw()
+        eventLoop!.pumpAppEvents(10, app)
+    }
+}
+
+;(hasDisplay ? test.serial : test.skip)('layers: order, payload replacement, swallow and remove', (t) => {
+    const calls: string[] = []
+    const received: unknown[] = []
+    const replacement = {type: 'Occluded', occluded: true} as const
+
+    const app = Application.withSyncRef({
+        onResumed,
+        onWindowEvent: (_activeLoop, _windowId, event) => {
+            if (event.type === 'RedrawRequested' || event === replacement) received.push(event)
+        },
+    })
+
+    const record = (name: string) => ({
+        onWindowEvent: (_activeLoop: ActiveEventLoop, _windowId: unknown, event: {type: string}) => {
+            if (event.type === 'RedrawRequested' || event === replacement) calls.push(name)
+        },
+    })
+    app.use(record('late'), 1)
+    app.use(record('first'))
+    app.use(record('second'))
+
+    pumpRedraws(app, () => received.length, 1)
+    t.deepEqual(calls.slice(0, 3), ['first', 'second', 'late'])
+    t.is((received[0] as {type: string}).type, 'RedrawRequested')
+
+    const replacing = app.use({
+        onWindowEvent: (_activeLoop, _windowId, event) => (event.type === 'RedrawRequested' ? replacement : undefined),
+    }, -1)
+    received.length = 0
+    pumpRedraws(app, () => received.length, 1)
+    t.is(received[0], replacement)
+    t.true(replacing.remove())
+    t.false(replacing.remove())
+    t.false(replacing.attached)
+
+    const swallowing = app.use({
+        onWindowEvent: (_activeLoop, _windowId, event) => event.type !== 'RedrawRequested',
+    }, -1)
+    received.length = 0
+    calls.length = 0
+    let swallowed = 0
+    const counting = app.use({
+        onWindowEvent: (_activeLoop, _windowId, event) => {
+            if (event.type === 'RedrawRequested') swallowed++
+        },
+    }, -2)
+    pumpRedraws(app, () => swallowed, 3)
+    t.true(swallowed >= 3)
+    t.deepEqual(received, [])
+    t.deepEqual(calls, [])
+
+    swallowing.remove()
+    counting.remove()
+    pumpRedraws(app, () => received.length, 1)
+    t.is((received[0] as {type: string}).type, 'RedrawRequested')
+})
+
+;(hasDisplay ? test.serial : test.skip)('layers: promises of async application hooks are still awaited', async (t) => {
+    let rejected = 0
+    const app = Application.withAsyncRef({
+        onResumed: async (activeLoop) => onResumed(activeLoop),
+        onWindowEvent: async (_activeLoop, _windowId, event) => {
+            if (event.type !== 'RedrawRequested') return
+            rejected++
+            throw new Error('handled natively')
+        },
+    })
+    app.use({onWindowEvent: () => undefined})
+
+    pumpRedraws(app, () => rejected, 1)
+    // an unawaited rejection would fail the test as unhandled
+    await new Promise((resolve) => setTimeout(resolve, 50))
+    t.true(rejected >= 1)
+})
@@ -0,0 +1,28 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('withOwnerWindow: throws where winit has no owner relation', (t) => {
+    const eventLoop = new EventLoop()
+    let checked = false
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            if (checked) return
+            checked = true
+            const owner = activeLoop.createWindow(new WindowAttributes().withVisible(false))
+            t.throws(() => new WindowAttributes().withOwnerWindow(owner), {
+                message: 'owner windows are only supported on Windows and macOS',
+            })
+            activeLoop.exit()
+        },
+        onWindowEvent: () => {},
+    })
+
+    for (let i = 0; i < 50 && !checked; i++) {
+        eventLoop.pumpAppEvents(10, app)
+    }
+    t.true(checked)
+})
@@ -0,0 +1,33 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+;(hasDisplay ? test : test.skip)('registry: closing a window destroys it right away', async (t) => {
+    const eventLoop = new EventLoop()
+    const events: string[] = []
+    let created = false
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            if (created) return
+            created = true
+            const window = activeLoop.createWindow(new WindowAttributes().withVisible(false))
+            t.is(activeLoop.windowCount(), 1)
+            t.true(activeLoop.closeWindow(window.id()))
+            t.is(activeLoop.windowCount(), 0)
+            t.throws(() => window.id(), {message: /closed/})
+            t.throws(() => window.setTitle('closed'), {message: /closed/})
+        },
+        onWindowEvent: (_activeLoop, _windowId, event) => {
+            events.push(event.type)
+        },
+    })
+
+    for (let i = 0; i < 50 && !events.includes('Destroyed'); i++) {
+        eventLoop.pumpAppEvents(10, app)
+        await new Promise((resolve) => setImmediate(resolve))
+    }
+    t.true(events.includes('Destroyed'))
+})
@@ -0,0 +1,62 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes, type ActiveEventLoop, type Window} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+// one event loop per process, shared by the tests of this file
+const eventLoop = hasDisplay ? new EventLoop() : undefined
+const stale = /only valid during the handler call it was passed to/
+
+;(hasDisplay ? test.serial : test.skip)('a kept ActiveEventLoop or LazyWindowEvent throws after its handler returned', (t) => {
+    let keptLoop: ActiveEventLoop | undefined
+    let keptEvent: {readonly type: string; readonly event: unknown} | undefined
+    let window: Window | undefined
+
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            keptLoop = activeLoop
+            window ??= activeLoop.createWindow(new WindowAttributes().withVisible(false))
+        },
+        onLazyWindowEvent: (_activeLoop, _windowId, lazy) => {
+            keptEvent ??= lazy
+        },
+    })
+
+    for (let i = 0; i < 50 && !keptEvent; i++) {
+        window?.requestRedraw()
+        eventLoop!.pumpAppEvents(10, app)
+    }
+
+    t.throws(() => keptLoop!.createWindow(new WindowAttributes()), {message: stale})
+    t.throws(() => keptLoop!.exiting(), {message: stale})
+    t.throws(() => keptLoop!.windowCount(), {message: stale})
+    t.throws(() => keptLoop!.windows(), {message: stale})
+    t.throws(() => keptLoop!.setEventBatchOptions({}), {message: stale})
+    t.throws(() => keptEvent!.event, {message: stale})
+    // the discriminant is a copy and stays readable
+    t.is(typeof keptEvent!.type, 'string')
+})
+
+;(hasDisplay ? test.serial : test.skip)('withAsyncFx2Safe: queued handlers can still use the ActiveEventLoop', async (t) => {
+    const errors: unknown[] = []
+    let window: Window | undefined
+    const app = Application.withAsyncFx2Safe({
+        onResumed: async (activeLoop) => {
+            try {
+                window ??= activeLoop.createWindow(new WindowAttributes().withVisible(false))
+            } catch (error) {
+                errors.push(error)
+            }
+        },
+        onWindowEvent: async () => {},
+    })
+
+    for (let i = 0; i < 50 && !window && errors.length === 0; i++) {
+        eventLoop!.pumpAppEvents(10, app)
+        // threadsafe calls are delivered once the pump returned
+        await new Promise((resolve) => setImmediate(resolve))
+    }
+    t.deepEqual(errors, [])
+    t.truthy(window)
+})
@@ -0,0 +1,68 @@
+import test from 'ava'
+
+import {Application, EventLoop, WindowAttributes, type ActiveEventLoop, type Window} from '../index.js'
+
+const hasDisplay = process.platform === 'linux' && Boolean(process.env.DISPLAY || process.env.WAYLAND_DISPLAY)
+
+const noop = () => {}
+
+test('setHandlers: handlers are checked up front', (t) => {
+    const app = Application.withSyncRef({onResumed: noop, onWindowEvent: noop})
+    t.throws(() => app.setHandlers(1 as never), {message: /handlers must be an object/})
+    t.throws(() => app.setHandlers({onWindowEvent: 5 as never}), {message: /`onWindowEvent` must be a function, null or undefined/})
+    t.throws(() => app.setHandlers({onResumed: null as never}), {message: /`onResumed` must be a function$/})
+    t.notThrows(() => app.setHandlers({onWindowEvent: noop, onResumed: noop}))
+})
+
+test('setHandlers: a window handler has to be left', (t) => {
+    const app = Application.withSyncRef({onResumed: noop, onWindowEvent: noop})
+    const message = /would leave no window handler/
+    t.throws(() => app.setHandlers({onWindowEvent: null}), {message})
+    t.notThrows(() => app.setHandlers({onWindowEvent: null, onLazyWindowEvent: noop}))
+    // checked against the swaps still waiting
+    t.throws(() => app.setHandlers({onLazyWindowEvent: null}), {message})
+    t.notThrows(() => app.setHandlers({onEvents: noop}))
+    t.notThrows(() => app.setHandlers({onLazyWindowEvent: null}))
+})
+
+test('setHandlers: only ref runners can swap', (t) => {
+    const message = /only be replaced on applications made with `withSyncRef` or `withAsyncRef`/
+    t.throws(() => Application.withSyncFx({onResumed: noop, onWindowEvent: noop}).setHandlers({}), {message})
+    t.throws(() => Application.withAsyncFx({onResumed: async () => {}, onWindowEvent: async () => {}}).setHandlers({}), {message})
+})
+
+;(hasDisplay ? test : test.skip)('setHandlers: swaps and clears before the next event', (t) => {
+    const eventLoop = new EventLoop()
+    const calls: string[] = []
+    let window: Window | undefined
+
+    const redraws = (name: string) => (_activeLoop: ActiveEventLoop, _windowId: unknown, event: {type: string}) => {
+        if (event.type === 'RedrawRequested') calls.push(name)
+    }
+    const app = Application.withSyncRef({
+        onResumed: (activeLoop) => {
+            window ??= activeLoop.createWindow(new WindowAttributes().withVisible(false))
+        },
+        onWindowEvent: redraws('old'),
+    })
+    const pump = (until: () => boolean) => {
+        for (let i = 0; i < 50 && !until(); i++) {
+            window?.requestRedraw()
+            eventLoop.pumpAppEvents(10, app)
+        }
+    }
+
+    pump(() => calls.includes('old'))
+    t.true(calls.includes('old'))
+
+    app.setHandlers({onWindowEvent: redraws('new')})
+    calls.length = 0
+    pump(() => calls.includes('new'))
+    t.true(calls.includes('new'))
+    t.false(calls.includes('old'))
+
+    app.setHandlers({onWindowEvent: null, onLazyWindowEvent: noop, onAboutToWait: () => calls.push('idle')})
+    calls.length = 0
+    pump(() => calls.length >= 3)
+    t.deepEqual([...new Set(calls)], ['idle'])
+})
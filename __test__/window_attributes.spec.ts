@@ -0,0 +1,41 @@
+import test from 'ava'
+
+import {WindowAttributes} from '../index.js'
+
+test('WindowAttributes: object round trip', (t) => {
+    const attributes = WindowAttributes.fromObject({
+        title: 'config',
+        innerSize: {type: 'Logical', width: 640, height: 480},
+        enabledButtons: ['CLOSE', 'MINIMIZE'],
+        resizable: false,
+    })
+
+    t.is(attributes.title, 'config')
+    t.false(attributes.resizable)
+    t.deepEqual(attributes.toObject().enabledButtons, ['CLOSE', 'MINIMIZE'])
+    t.deepEqual(attributes.enabledButtons.toNames(), ['CLOSE', 'MINIMIZE'])
+    t.deepEqual(WindowAttributes.fromObject(attributes.toObject()).toObject(), attributes.toObject())
+})
+
+test('WindowAttributes: merge keeps unchanged fields', (t) => {
+    const base = new WindowAttributes().withTitle('base').withVisible(false)
+    base.merge(new WindowAttributes().withDecorations(false))
+
+    t.is(base.title, 'base')
+    t.false(base.visible)
+    t.false(base.decorations)
+})
+
+test('WindowAttributes: merge resets fields set to their default', (t) => {
+    const base = new WindowAttributes().withTitle('base').withResizable(false)
+    base.merge(WindowAttributes.fromObject({resizable: true}))
+
+    t.is(base.title, 'base')
+    t.true(base.resizable)
+})
+
+test('WindowAttributes: unknown button errors', (t) => {
+    t.throws(() => WindowAttributes.fromObject({enabledButtons: ['help' as any]}), {
+        message: /unknown flag `help` for WindowButtons/
+    })
+})
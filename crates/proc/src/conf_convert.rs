@@ -42,6 +42,12 @@ define_const_str!(
     META_SKIP_BACKWARD = skip_backward,
 );
 
+pub(crate) const CONVERT_METAS: &[&str] = &[
+    META_SKIP_FROM_ORIGIN, META_SKIP_INTO_ORIGIN,
+    META_SKIP_TO_JS, META_SKIP_FROM_JS,
+    META_SKIP_FORWARD, META_SKIP_BACKWARD,
+];
+
 pub(crate) fn parse_conf_convert(metas: &Vec<Meta>) -> ConfConvert {
     map_meta_to_local!(&metas => {
         META_SKIP_FROM_ORIGIN => skip_from_origin,
@@ -1,12 +1,12 @@
 use crate::{
-    utils::{get_meta_value_as, append_to_tokens, get_metas_by_attr_name, separate_attr_by_name},
-    conf_usage::{get_meta_value_as_conf_usage, ConfUsage},
+    utils::{get_meta_value_as, append_to_tokens, check_metas, get_metas_by_attr_name, separate_attr_by_name},
+    conf_usage::{get_option_conf_usage, ConfUsage},
 };
 use macros::{define_const_str, map_meta_to_local};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
-use syn::{Attribute, Field, Fields, Ident};
+use syn::{Attribute, Error, Field, Fields, Ident};
 
 pub(crate) struct ConfFields {
     pub kind: FieldsKind,
@@ -24,10 +24,11 @@ pub(crate) struct ConfField {
 const META_FIELD_NAME: &str = "field_name";
 const META_FROM_ORIGIN: &str = "from_origin";
 const META_INTO_ORIGIN: &str = "into_origin";
+const FIELD_METAS: &[&str] = &[META_FIELD_NAME, META_FROM_ORIGIN, META_INTO_ORIGIN];
 
-pub(crate) fn parse_conf_fields(fields: &Fields, parent_attrs: &Vec<Attribute>, attr_proxy_name: &str, attr_includes: &[&str]) -> ConfFields {
-    ConfFields {
-        kind: parse_fields_kind(fields, parent_attrs, attr_proxy_name),
+pub(crate) fn parse_conf_fields(fields: &Fields, parent_attrs: &Vec<Attribute>, attr_proxy_name: &str, attr_includes: &[&str]) -> syn::Result<ConfFields> {
+    Ok(ConfFields {
+        kind: parse_fields_kind(fields, parent_attrs, attr_proxy_name)?,
         fields: fields.iter()
             .zip(0..fields.len())
             .map(|(field, idx)| {
@@ -35,30 +36,30 @@ pub(crate) fn parse_conf_fields(fields: &Fields, parent_attrs: &Vec<Attribute>,
 
                 let (matched, surplus) = separate_attr_by_name(attrs, attr_includes);
 
-                map_meta_to_local!(&get_metas_by_attr_name(&matched, attr_proxy_name) => {
+                let metas = get_metas_by_attr_name(&matched, attr_proxy_name)?;
+                check_metas(&metas, FIELD_METAS)?;
+                map_meta_to_local!(&metas => {
                     META_FIELD_NAME => field_name,
                     META_FROM_ORIGIN => from_origin,
                     META_INTO_ORIGIN => into_origin,
                 });
 
                 let field_ident = field_name.as_ref()
-                    .and_then(get_meta_value_as::<Ident>)
+                    .map(get_meta_value_as::<Ident>)
+                    .transpose()?
                     .or_else(|| ident.clone())
                     .unwrap_or_else(|| format_ident!("field_{}", idx));
 
-                let from_origin = from_origin.as_ref().and_then(get_meta_value_as_conf_usage);
-                let into_origin = into_origin.as_ref().and_then(get_meta_value_as_conf_usage);
-
-                ConfField {
+                Ok(ConfField {
                     input: field.clone(),
                     reserved_attrs: surplus,
                     field_ident,
-                    from_origin,
-                    into_origin,
-                }
+                    from_origin: get_option_conf_usage(&from_origin)?,
+                    into_origin: get_option_conf_usage(&into_origin)?,
+                })
             })
-            .collect(),
-    }
+            .collect::<syn::Result<_>>()?,
+    })
 }
 
 impl ToTokens for ConfField {
@@ -117,17 +118,20 @@ define_const_str!(
     META_NAMED = Named,
     META_UNNAMED = Unnamed,
 );
+pub(crate) const KIND_METAS: &[&str] = &[META_NAMED, META_UNNAMED];
 
-pub(crate) fn parse_fields_kind(fields: &Fields, attrs: &Vec<Attribute>, attr_proxy_name: &str) -> FieldsKind {
-    map_meta_to_local!(&get_metas_by_attr_name(&attrs, attr_proxy_name) => {
+pub(crate) fn parse_fields_kind(fields: &Fields, attrs: &Vec<Attribute>, attr_proxy_name: &str) -> syn::Result<FieldsKind> {
+    let metas = get_metas_by_attr_name(&attrs, attr_proxy_name)?;
+    check_metas(&metas, KIND_METAS)?;
+    map_meta_to_local!(&metas => {
         META_NAMED => meta_named,
         META_UNNAMED => meta_unnamed,
     });
 
-    match (meta_named, meta_unnamed) {
+    Ok(match (meta_named, meta_unnamed) {
         (None, None) => FieldsKind::from(fields),
-        (Some(_), Some(_)) => panic!("unexpected both Named and Unnamed"),
+        (Some(_), Some(unnamed)) => return Err(Error::new_spanned(unnamed, "`Named` and `Unnamed` exclude each other")),
         (Some(_), _) => FieldsKind::Named,
         (_, Some(_)) => FieldsKind::Unnamed,
-    }
+    })
 }
\ No newline at end of file
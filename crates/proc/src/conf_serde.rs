@@ -0,0 +1,25 @@
+use convert_case::Case;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{LitStr, Meta};
+use crate::utils::{get_meta_by_name, to_case};
+
+pub(crate) const META_SERDE: &str = "serde";
+
+/// `serde` opts a proxy type into `Serialize`/`Deserialize`, named like its js object
+pub(crate) fn parse_conf_serde(metas: &Vec<Meta>) -> bool {
+    get_meta_by_name(metas, META_SERDE).is_some()
+}
+
+pub(crate) fn js_name_of(ident: &Ident) -> LitStr {
+    LitStr::new(&to_case(ident.to_string(), Case::Camel), ident.span())
+}
+
+pub(crate) fn quote_serde_derive() -> TokenStream {
+    quote! { #[derive(::serde::Serialize, ::serde::Deserialize)] }
+}
+
+pub(crate) fn quote_serde_rename(ident: &Ident) -> TokenStream {
+    let js_name = js_name_of(ident);
+    quote! { #[serde(rename = #js_name)] }
+}
@@ -1,6 +1,6 @@
 use proc_macro2::TokenStream;
 use quote::{quote, quote_spanned, ToTokens};
-use syn::{Expr, ExprBlock, ExprCall, ExprClosure, ExprMethodCall, ExprPath, Meta, MetaNameValue};
+use syn::{Error, Expr, ExprBlock, ExprCall, ExprClosure, ExprMethodCall, ExprPath, Meta, MetaNameValue};
 use syn::spanned::Spanned;
 
 pub(crate) enum ConfUsage {
@@ -33,21 +33,28 @@ pub(crate) fn quote_conf_usage(from: &dyn ToTokens, conf_usage: &ConfUsage) -> T
     }
 }
 
-pub(crate) fn get_meta_value_as_conf_usage(meta: &Meta) -> Option<ConfUsage> {
+pub(crate) fn get_meta_value_as_conf_usage(meta: &Meta) -> syn::Result<ConfUsage> {
     let Meta::NameValue(MetaNameValue { value, .. }) = meta
-    else { return None };
+    else { return Err(Error::new_spanned(meta, "expected a converter, like `name = path::to::fn`")) };
 
-    Some(expr_to_conf_usage(value))
+    expr_to_conf_usage(value)
 }
 
-fn expr_to_conf_usage(value: &Expr) -> ConfUsage {
-    match value {
+pub(crate) fn get_option_conf_usage(meta: &Option<Meta>) -> syn::Result<Option<ConfUsage>> {
+    meta.as_ref().map(get_meta_value_as_conf_usage).transpose()
+}
+
+fn expr_to_conf_usage(value: &Expr) -> syn::Result<ConfUsage> {
+    Ok(match value {
         Expr::Path(path) => ConfUsage::Path(path.clone()),
         Expr::Closure(closure) => ConfUsage::Closure(closure.clone()),
-        Expr::Array(array) => ConfUsage::Pipe(array.elems.iter().map(expr_to_conf_usage).collect()),
+        Expr::Array(array) => ConfUsage::Pipe(array.elems.iter().map(expr_to_conf_usage).collect::<syn::Result<_>>()?),
         Expr::MethodCall(method_call) => ConfUsage::MethodCall(method_call.clone()),
         Expr::Block(block) => ConfUsage::Block(block.clone()),
         Expr::Call(call) => ConfUsage::Call(call.clone()),
-        _ => panic!("unexpected converter"),
-    }
+        _ => return Err(Error::new_spanned(
+            value,
+            "unexpected converter, expected a path, closure, call, method call, block or an array of them",
+        )),
+    })
 }
\ No newline at end of file
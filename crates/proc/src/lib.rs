@@ -5,12 +5,14 @@ use macros::for_proc;
 mod utils;
 mod conf_fields;
 mod conf_convert;
+mod conf_serde;
 mod proxy_enum;
 mod proxy_struct;
 mod proxy_wrap;
 mod proxy_impl;
 mod proxy_flags;
 mod conf_usage;
+mod ts_type;
 
 use proc_macro::TokenStream;
 
@@ -3,10 +3,12 @@ use std::{
     mem::transmute,
 };
 use crate::{
-    utils::{append_to_tokens, get_meta_value_as, get_type_ty_or, parse_metas, separate_attr_by_name},
-    conf_convert::{parse_conf_convert, ConfConvert, NormalConfConvert},
+    utils::{append_to_tokens, check_metas, check_no_nested, get_ident_optional, get_type_ty_or, parse_metas, separate_attr_by_name},
+    conf_convert::{parse_conf_convert, ConfConvert, NormalConfConvert, CONVERT_METAS},
     conf_fields::{parse_conf_fields, quote_conf_fields, ConfField, ConfFields, Kind, With},
+    conf_serde::{parse_conf_serde, quote_serde_derive, quote_serde_rename, META_SERDE},
     conf_usage::quote_option_conf_usage,
+    ts_type::field_ts_type,
     utils::parse_as,
 };
 use macros::{define_const_str, map_meta_to_local};
@@ -16,10 +18,9 @@ use syn::{parse_macro_input, spanned::Spanned, Attribute, BinOp, Expr, ExprBinar
 use crate::utils::get_metas_by_attr_name;
 
 pub(crate) fn proxy_enum(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let metas = parse_metas(attrs);
     let item_enum = parse_macro_input!(input as ItemEnum);
 
-    match parse_proxy_enum(&metas, &item_enum) {
+    match parse_metas(attrs).and_then(|metas| parse_proxy_enum(&metas, &item_enum)) {
         Ok(proxy_enum) => proc_macro::TokenStream::from(quote! { #proxy_enum }),
         Err(err) => err.to_compile_error().into(),
     }
@@ -44,6 +45,7 @@ struct ProxyEnum {
     pub non_exhaustive: bool,
     pub conf_convert: ConfConvert,
     pub conf_code: Option<ConfCode>,
+    pub serde: bool,
 }
 
 #[derive(Clone)]
@@ -57,21 +59,20 @@ enum ProxyVariants {
     Constants(Vec<ProxyVariant>),
 }
 
-fn get_repr_type(attrs: &Vec<Attribute>) -> Type {
-    get_metas_by_attr_name(attrs, "repr").first()
+fn get_repr_type(attrs: &Vec<Attribute>) -> Result<Type, Error> {
+    Ok(get_metas_by_attr_name(attrs, "repr")?.first()
         .map(Meta::path)
         .and_then(Path::get_ident)
         .map(|ident| parse_as::<Type>(ident))
-        .unwrap_or_else(|| parse_as::<Type>(&quote!(u8)))
+        .unwrap_or_else(|| parse_as::<Type>(&quote!(u8))))
 }
 
 fn parse_proxy_enum(metas: &Vec<Meta>, item_enum: &ItemEnum) -> Result<ProxyEnum, Error> {
     let ItemEnum { attrs, ident, variants, .. } = item_enum;
 
     let (matched, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
-    if matches!(matched.len(), n if n > 0) {
-        return Err(Error::new(item_enum.span(), "so many proxy_enum attributes"));
-    }
+    check_no_nested(&matched, ATTR_PROXY_ENUM)?;
+    check_metas(metas, &[&[META_ORIGIN_ENUM, META_STRING_ENUM, META_NON_EXHAUSTIVE, META_CODE_NAME, META_SERDE], CONVERT_METAS].concat())?;
 
     map_meta_to_local!(&metas => {
         META_ORIGIN_ENUM => origin_type,
@@ -80,31 +81,30 @@ fn parse_proxy_enum(metas: &Vec<Meta>, item_enum: &ItemEnum) -> Result<ProxyEnum
         META_CODE_NAME => code_name,
     });
 
-    let code_name = code_name.as_ref()
-        .and_then(get_meta_value_as::<Ident>)
-        .map(|code_name| ConfCode { code_name, code_type: get_repr_type(&surplus) })
-        .or_else(|| {
-            let has_discriminant = variants.iter()
-                .any(|Variant { discriminant, .. }| discriminant.is_some());
-            if !has_discriminant { return None; }
-
-            Some(ConfCode {
-                code_name: format_ident!("discriminant"),
-                code_type: get_repr_type(&surplus),
-            })
-        });
+    let has_discriminant = variants.iter()
+        .any(|Variant { discriminant, .. }| discriminant.is_some());
+    let code_name = match get_ident_optional(&code_name)? {
+        Some(code_name) => Some(ConfCode { code_name, code_type: get_repr_type(&surplus)? }),
+        None if has_discriminant => Some(ConfCode {
+            code_name: format_ident!("discriminant"),
+            code_type: get_repr_type(&surplus)?,
+        }),
+        None => None,
+    };
 
-    let proxy_variants = parse_proxy_variants(string_enum.is_some(), &code_name, variants.iter().collect())?;
+    let serde = parse_conf_serde(metas);
+    let proxy_variants = parse_proxy_variants(string_enum.is_some(), serde, &code_name, variants.iter().collect())?;
 
     Ok(ProxyEnum {
         input: item_enum.clone(),
         reserved_attrs: surplus,
         proxy_variants,
-        origin_type: get_type_ty_or(&origin_type, &format_ident!("Origin{}", ident)),
+        origin_type: get_type_ty_or(&origin_type, &format_ident!("Origin{}", ident))?,
         string_enum: string_enum.is_some(),
         non_exhaustive: non_exhaustive.is_some(),
         conf_convert: parse_conf_convert(metas),
         conf_code: code_name,
+        serde,
     })
 }
 
@@ -112,7 +112,7 @@ impl ToTokens for ProxyEnum {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             input, reserved_attrs, proxy_variants,
-            origin_type, string_enum, non_exhaustive, conf_convert, conf_code
+            origin_type, string_enum, non_exhaustive, conf_convert, conf_code, serde
         } = self;
 
         let ItemEnum { ident, vis, .. } = input;
@@ -123,10 +123,20 @@ impl ToTokens for ProxyEnum {
         if skip_to_js { napi_metas.push(quote! {object_to_js = false}) }
         if skip_from_js { napi_metas.push(quote! {object_from_js = false}) }
 
-        let non_exhaustive_variant = if !(*non_exhaustive) { vec![] } else { vec![quote! { NonExhaustive }] };
+        let non_exhaustive_variant = match (*non_exhaustive, *string_enum) {
+            (false, _) => vec![],
+            (true, true) => vec![quote! { NonExhaustive }],
+            (true, false) => vec![quote_non_exhaustive_variant(*serde)],
+        };
+
+        let mut serde_attrs = Vec::new();
+        if *serde { serde_attrs.push(quote_serde_derive()) }
+        // same discriminant napi uses for structured enums
+        if *serde && !*string_enum { serde_attrs.push(quote! { #[serde(tag = "type")] }) }
 
         append_to_tokens(tokens, quote_spanned! { input.span() =>
             #[napi( #( #napi_metas ),* )]
+            #( #serde_attrs )*
             #( #reserved_attrs )*
             #vis enum #ident {
                 #( #proxy_variants, )*
@@ -151,7 +161,7 @@ impl ToTokens for ProxyEnum {
             let non_exhaustive_from = if !(*non_exhaustive) {
                 vec![]
             } else {
-                vec![quote! { _ => Self::NonExhaustive }]
+                vec![if *string_enum { quote! { _ => Self::NonExhaustive } } else { quote! { _ => Self::NonExhaustive { non_exhaustive: None } } }]
             };
 
             append_to_tokens(tokens, quote! {
@@ -185,7 +195,7 @@ impl ToTokens for ProxyEnum {
             let non_exhaustive_into = if !(*non_exhaustive) {
                 vec![]
             } else {
-                vec![quote! { Self::NonExhaustive => unreachable!(stringify!(#ident::NonExhaustive)) }]
+                vec![quote! { Self::NonExhaustive { .. } => unreachable!(stringify!(#ident::NonExhaustive)) }]
             };
 
             append_to_tokens(tokens, quote! {
@@ -277,31 +287,33 @@ struct ProxyVariant {
     pub conf_fields: ConfFields,
     pub string_enum: bool,
     pub conf_code: Option<ConfCode>,
+    pub serde: bool,
 }
 
-fn parse_proxy_variants(string_enum: bool, code_info: &Option<ConfCode>, variants: Vec<&Variant>) -> Result<Vec<ProxyVariant>, Error> {
+fn parse_proxy_variants(string_enum: bool, serde: bool, code_info: &Option<ConfCode>, variants: Vec<&Variant>) -> Result<Vec<ProxyVariant>, Error> {
     let proxy_variants = variants.iter()
         .map(|variant| {
             let Variant { attrs, fields, .. } = variant;
 
             let (matched, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
 
-            ProxyVariant {
+            Ok(ProxyVariant {
                 input: (*variant).clone(),
                 reserved_attrs: surplus,
-                conf_fields: parse_conf_fields(fields, &matched, ATTR_PROXY_ENUM, ATTR_INCLUDES),
+                conf_fields: parse_conf_fields(fields, &matched, ATTR_PROXY_ENUM, ATTR_INCLUDES)?,
                 string_enum,
                 conf_code: code_info.clone(),
-            }
+                serde,
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, Error>>()?;
 
     Ok(proxy_variants)
 }
 
 impl ToTokens for ProxyVariant {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let Self { input, reserved_attrs, conf_fields, string_enum, conf_code, .. } = self;
+        let Self { input, reserved_attrs, conf_fields, string_enum, conf_code, serde, .. } = self;
         let Variant { ident, discriminant, .. } = input;
         let ConfFields { fields, .. } = conf_fields;
 
@@ -313,9 +325,11 @@ impl ToTokens for ProxyVariant {
                 napi_metas.push(quote! { ts_type = #expr });
             }
 
+            let serde_rename = serde.then(|| quote_serde_rename(code_name));
             append_to_tokens(tokens, quote_spanned! { input.span() =>
                 #( #reserved_attrs )* #ident {
                     #[napi( #( #napi_metas ),* )]
+                    #serde_rename
                     #code_name: #code_type
                 }
             });
@@ -329,9 +343,101 @@ impl ToTokens for ProxyVariant {
             });
             return;
         }
+        let fields = fields.iter().map(|field| quote_variant_field(field, *serde));
         append_to_tokens(tokens, quote_spanned! { input.span() =>
             #( #reserved_attrs )*
             #ident { #( #fields ),* }
         });
     }
-}
\ No newline at end of file
+}
+
+/// napi types every variant as `{ type: 'Name', ...fields }` and has no way to type one differently,
+/// so the catch-all member `{ type: string }` is spliced in through the ts type of a field that is never set
+fn quote_non_exhaustive_variant(serde: bool) -> TokenStream {
+    let serde_skip = serde.then(|| quote! { #[serde(skip)] });
+    quote! {
+        NonExhaustive {
+            #[napi(js_name = "nonExhaustive", ts_type = "never } | { type: string")]
+            #serde_skip
+            non_exhaustive: Option<()>
+        }
+    }
+}
+
+/// Pin the field type in the generated union, unless the field has its own napi attribute
+fn quote_variant_field(field: &ConfField, serde: bool) -> TokenStream {
+    let serde_rename = serde.then(|| quote_serde_rename(&field.field_ident));
+    let has_napi_attr = field.reserved_attrs.iter().any(|attr| attr.path().is_ident("napi"));
+    if has_napi_attr {
+        return quote! { #serde_rename #field };
+    }
+    let ts_type = Literal::string(&field_ts_type(&field.input.ty));
+    quote! { #[napi(ts_type = #ts_type)] #serde_rename #field }
+}
+#[cfg(test)]
+mod tests {
+    use super::parse_proxy_enum;
+    use quote::{quote, ToTokens};
+    use syn::{parse::Parser, parse_quote, punctuated::Punctuated, Fields, ItemEnum, LitStr, Meta, Token, Type};
+
+    fn expand(metas: proc_macro2::TokenStream, item_enum: ItemEnum) -> ItemEnum {
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(metas).unwrap().into_iter().collect();
+        let tokens = parse_proxy_enum(&metas, &item_enum).unwrap().into_token_stream();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+        file.items.into_iter()
+            .find_map(|item| match item {
+                syn::Item::Enum(item_enum) => Some(item_enum),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// The union as napi prints it into index.d.ts, from the `ts_type` and `js_name` of each field
+    fn render_union(item_enum: &ItemEnum) -> String {
+        item_enum.variants.iter()
+            .map(|variant| {
+                let mut members = vec![format!("type: '{}'", variant.ident)];
+                if let Fields::Named(fields) = &variant.fields {
+                    for field in &fields.named {
+                        let (mut js_name, mut ts_type) = (field.ident.as_ref().unwrap().to_string(), String::new());
+                        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("napi")) {
+                            attr.parse_nested_meta(|meta| {
+                                let value = meta.value()?.parse::<LitStr>()?.value();
+                                if meta.path.is_ident("js_name") { js_name = value } else { ts_type = value }
+                                Ok(())
+                            }).unwrap();
+                        }
+                        let optional = matches!(&field.ty, Type::Path(path) if path.path.segments.last().unwrap().ident == "Option");
+                        members.push(format!("{js_name}{}: {ts_type}", if optional { "?" } else { "" }));
+                    }
+                }
+                format!("| {{ {} }}", members.join("; "))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_structured_union() {
+        let item_enum = expand(quote!(origin_type = origin::Event), parse_quote! {
+            pub enum Event {
+                Moved { position: Position, delta: Option<f64> },
+                Closed,
+            }
+        });
+        assert_eq!(render_union(&item_enum), "| { type: 'Moved'; position: Position; delta?: number } | { type: 'Closed' }");
+    }
+
+    #[test]
+    fn test_non_exhaustive_catch_all() {
+        let item_enum = expand(quote!(origin_type = origin::Event, non_exhaustive), parse_quote! {
+            pub enum Event {
+                Closed,
+            }
+        });
+        assert_eq!(
+            render_union(&item_enum),
+            "| { type: 'Closed' } | { type: 'NonExhaustive'; nonExhaustive?: never } | { type: string }",
+        );
+    }
+}
@@ -1,40 +1,63 @@
-use crate::utils::{append_to_tokens, get_meta_by_name, get_meta_value_as, get_type_ty_or, parse_as, parse_metas};
-use proc_macro2::TokenStream;
+use crate::{
+    conf_serde::{parse_conf_serde, META_SERDE},
+    utils::{append_to_tokens, check_metas, get_meta_by_name, get_meta_value_as, get_type_ty_or, parse_metas},
+};
+use macros::define_const_str;
+use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
-use syn::{parse_macro_input, ExprTuple, Ident, ItemStruct, Type};
+use syn::{parse_macro_input, Error, Expr, ExprParen, ExprPath, ExprTuple, Ident, ItemStruct, LitStr, Meta, Type};
 
 pub(crate) fn proxy_flags(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let metas = parse_metas(attrs);
     let input = parse_macro_input!(input as ItemStruct);
 
+    match parse_metas(attrs).and_then(|metas| parse_proxy_flags(&metas, input)) {
+        Ok(proxy_flags) => proc_macro::TokenStream::from(quote! { #proxy_flags }),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+define_const_str!(
+    META_ORIGIN = origin,
+    META_FLAGS = flags,
+);
+
+fn parse_proxy_flags(metas: &Vec<Meta>, input: ItemStruct) -> syn::Result<ProxyFlags> {
+    check_metas(metas, &[META_ORIGIN, META_FLAGS, META_SERDE])?;
+
     let origin = get_type_ty_or(
-        &get_meta_by_name(&metas, "origin"),
+        &get_meta_by_name(metas, META_ORIGIN),
         &format_ident!("Origin{}", input.ident),
-    );
-
-    let bitflags = get_meta_by_name(&metas, "flags");
+    )?;
 
-    let Some(flags_meta) = bitflags
-    else { panic!("`flags` must assigned on #[proxy_flags]") };
+    let Some(flags_meta) = get_meta_by_name(metas, META_FLAGS)
+    else { return Err(Error::new(Span::call_site(), "`flags` must be assigned on #[proxy_flags], like `flags = (FLAG_A, FLAG_B)`")) };
 
-    let Some(expr_tuple) = get_meta_value_as::<ExprTuple>(&flags_meta)
-    else { panic!("value for `flags` must like a tuple (FLAG_A, FLAG_B, FLAG_C)") };
+    let elems: Vec<Expr> = match get_meta_value_as::<Expr>(&flags_meta)? {
+        Expr::Tuple(ExprTuple { elems, .. }) => elems.into_iter().collect(),
+        Expr::Paren(ExprParen { expr, .. }) => vec![*expr],
+        value => return Err(Error::new_spanned(value, "value for `flags` must be a tuple, like `(FLAG_A, FLAG_B, FLAG_C)`")),
+    };
 
-    let flags: Vec<_> = expr_tuple.elems.iter().map(|el| parse_as::<Ident>(el)).collect();
+    let flags = elems.iter()
+        .map(|el| match el {
+            Expr::Path(ExprPath { path, .. }) => path.get_ident().cloned(),
+            _ => None,
+        }.ok_or_else(|| Error::new_spanned(el, "expected the name of a flag")))
+        .collect::<syn::Result<Vec<_>>>()?;
 
-    let proxy_flags = ProxyFlags {
+    Ok(ProxyFlags {
         input,
         origin,
         flags,
-    };
-
-    proc_macro::TokenStream::from(quote! { #proxy_flags })
+        serde: parse_conf_serde(metas),
+    })
 }
 
 struct ProxyFlags {
     pub input: ItemStruct,
     pub origin: Type,
     pub flags: Vec<Ident>,
+    pub serde: bool,
 }
 
 impl ToTokens for ProxyFlags {
@@ -43,6 +66,7 @@ impl ToTokens for ProxyFlags {
             input: ItemStruct { ident: name, attrs, .. },
             origin: origin_ty,
             flags,
+            serde,
         } = self;
 
         let lower_names: Vec<_> = flags.iter().map(|flag| format_ident!("{}", flag.to_string().to_lowercase())).collect();
@@ -169,11 +193,131 @@ impl ToTokens for ProxyFlags {
             )*
         });
 
+        append_to_tokens(&mut fns, quote_set_algebra(name, flags, &flag_idents));
+
         append_to_tokens(tokens, quote_spanned! { name.span() =>
             #[napi]
             impl #name {
                 #fns
             }
         });
+
+        if *serde {
+            append_to_tokens(tokens, quote_serde(name, flags, &flag_idents));
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Operations between two sets, and the names of the flags as written on the origin type
+fn quote_set_algebra(name: &Ident, flags: &[Ident], flag_idents: &[TokenStream]) -> TokenStream {
+    let flag_names: Vec<_> = flags.iter().map(|flag| LitStr::new(&flag.to_string(), flag.span())).collect();
+    let expected = LitStr::new(&flags.iter().map(Ident::to_string).collect::<Vec<_>>().join(", "), name.span());
+    let class_name = LitStr::new(&name.to_string(), name.span());
+
+    quote! {
+        #[napi]
+        pub fn union(&self, other: &#name) -> Self {
+            Self { #( #flag_idents: self.#flag_idents || other.#flag_idents ),* }
+        }
+        #[napi]
+        pub fn intersection(&self, other: &#name) -> Self {
+            Self { #( #flag_idents: self.#flag_idents && other.#flag_idents ),* }
+        }
+        #[napi]
+        pub fn difference(&self, other: &#name) -> Self {
+            Self { #( #flag_idents: self.#flag_idents && !other.#flag_idents ),* }
+        }
+        #[napi]
+        pub fn symmetric_difference(&self, other: &#name) -> Self {
+            Self { #( #flag_idents: self.#flag_idents != other.#flag_idents ),* }
+        }
+        #[napi]
+        pub fn complement(&self) -> Self {
+            Self { #( #flag_idents: !self.#flag_idents ),* }
+        }
+        /// Whether every flag of `other` is set
+        #[napi]
+        pub fn contains(&self, other: &#name) -> bool {
+            true #( && (self.#flag_idents || !other.#flag_idents) )*
+        }
+        /// Whether any flag of `other` is set
+        #[napi]
+        pub fn intersects(&self, other: &#name) -> bool {
+            false #( || (self.#flag_idents && other.#flag_idents) )*
+        }
+        #[napi]
+        pub fn equals(&self, other: &#name) -> bool {
+            true #( && self.#flag_idents == other.#flag_idents )*
+        }
+        #[napi(ts_return_type="this")]
+        pub fn insert(&mut self, other: &#name) -> &Self {
+            #( self.#flag_idents |= other.#flag_idents; )*
+            self
+        }
+        #[napi(ts_return_type="this")]
+        pub fn remove(&mut self, other: &#name) -> &Self {
+            #( self.#flag_idents &= !other.#flag_idents; )*
+            self
+        }
+        #[napi(ts_return_type="this")]
+        pub fn toggle(&mut self, other: &#name) -> &Self {
+            #( self.#flag_idents ^= other.#flag_idents; )*
+            self
+        }
+        /// Names are matched ignoring case, e.g. `['SHIFT', 'control']`
+        #[napi(factory)]
+        pub fn from_names(names: Vec<String>) -> napi::Result<Self> {
+            let mut flags = Self::empty();
+            for name in names {
+                match name.to_ascii_uppercase().as_str() {
+                    #( #flag_names => flags.#flag_idents = true, )*
+                    _ => return Err(napi::Error::from_reason(format!(
+                        "unknown flag `{name}` for {}, expected one of: {}", #class_name, #expected,
+                    ))),
+                }
+            }
+            Ok(flags)
+        }
+        #[napi]
+        pub fn to_names(&self) -> Vec<String> {
+            let mut names = Vec::new();
+            #( if self.#flag_idents { names.push(#flag_names.to_owned()) } )*
+            names
+        }
+        /// Like `SHIFT | CONTROL`, empty for no flag
+        #[napi(js_name = "toString")]
+        pub fn to_js_string(&self) -> String {
+            self.to_names().join(" | ")
+        }
+    }
+}
+
+/// Serialized as the array of the names of the set flags, the same names as `toNames`/`fromNames`
+fn quote_serde(name: &Ident, flags: &[Ident], flag_idents: &[TokenStream]) -> TokenStream {
+    let flag_names: Vec<_> = flags.iter().map(|flag| LitStr::new(&flag.to_string(), flag.span())).collect();
+
+    quote! {
+        impl ::serde::Serialize for #name {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeSeq;
+                let mut seq = serializer.serialize_seq(::core::option::Option::None)?;
+                #( if self.#flag_idents { seq.serialize_element(#flag_names)?; } )*
+                seq.end()
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for #name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                let names = <::std::vec::Vec<::std::string::String> as ::serde::Deserialize>::deserialize(deserializer)?;
+                let mut flags = Self { #( #flag_idents: false ),* };
+                for name in names {
+                    match name.to_ascii_uppercase().as_str() {
+                        #( #flag_names => flags.#flag_idents = true, )*
+                        _ => return ::core::result::Result::Err(<D::Error as ::serde::de::Error>::unknown_variant(&name, &[#( #flag_names ),*])),
+                    }
+                }
+                ::core::result::Result::Ok(flags)
+            }
+        }
+    }
+}
@@ -1,27 +1,27 @@
 use crate::{
-    conf_usage::{get_meta_value_as_conf_usage, quote_option_conf_usage},
-    utils::{append_to_tokens, get_meta_value_as, get_metas_by_attr_name, parse_as, parse_metas, separate_attr_by_name},
+    conf_usage::{get_option_conf_usage, quote_option_conf_usage, ConfUsage},
+    utils::{append_to_tokens, check_metas, check_no_nested, get_meta_value_as, get_metas_by_attr_name, parse_metas, separate_attr_by_name},
 };
 use macros::{define_const_str, map_meta_to_local};
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
 use std::fmt::Debug;
 use syn::{
-    parse,
+    parse2,
     parse::{Parse, ParseStream},
     parse_macro_input,
     punctuated::Punctuated, token::Pub,
-    Attribute, Expr, FnArg, ImplItem, ImplItemFn, ItemImpl, Meta, Pat, PatIdent, PatType, Receiver, ReturnType, Signature, Token, TraitItemFn, Visibility,
+    parse_quote, spanned::Spanned,
+    Attribute, Error, Expr, FnArg, ImplItem, ImplItemFn, ItemImpl, Meta, Pat, PatIdent, PatType, Receiver, ReturnType, Signature, Token, TraitItemFn, Type, TypePath, Visibility,
 };
 
 pub(crate) fn proxy_impl(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let metas = parse_metas(attrs);
-
     let item_impl = parse_macro_input!(input as ItemImpl);
 
-    let proxy_impl = parse_proxy_impl(&metas, &item_impl);
-
-    proc_macro::TokenStream::from(quote! { #proxy_impl })
+    match parse_metas(attrs).and_then(|metas| parse_proxy_impl(&metas, &item_impl)) {
+        Ok(proxy_impl) => proc_macro::TokenStream::from(quote! { #proxy_impl }),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
 
 pub(crate) struct ProxyImpl {
@@ -32,8 +32,7 @@ pub(crate) struct ProxyImpl {
 }
 
 pub(crate) enum ProxyImplItem {
-    TraitFnLike(TraitFnLike),
-    ImplFn(ImplItemFn),
+    TraitFnLike(TraitFnLike, ConfItem),
 }
 
 pub(crate) struct TraitFnLike {
@@ -42,6 +41,19 @@ pub(crate) struct TraitFnLike {
     pub sig: Signature,
 }
 
+pub(crate) struct ConfItem {
+    pub access_expr: Option<Expr>,
+    pub conv_return: Option<ConfUsage>,
+    pub skip_conv_return: bool,
+    pub args: Vec<ConfArg>,
+}
+
+pub(crate) struct ConfArg {
+    pub name: Ident,
+    pub conv_arg: Option<ConfUsage>,
+    pub skip_conv_arg: bool,
+}
+
 define_const_str!(ATTR_PROXY_IMPL = proxy_impl);
 const ATTR_INCLUDES: &[&str] = &[ATTR_PROXY_IMPL];
 
@@ -55,6 +67,8 @@ define_const_str!(
     META_CONV_RETURN = conv_return,
     META_SKIP_CONV_RETURN = skip_conv_return,
 );
+const ITEM_METAS: &[&str] = &[META_ACCESS_EXPR, META_CONV_RETURN, META_SKIP_CONV_RETURN];
+const ARG_METAS: &[&str] = &[META_CONV_ARG, META_SKIP_CONV_ARG];
 
 impl Parse for TraitFnLike {
     fn parse(input: ParseStream) -> syn::Result<Self> {
@@ -68,151 +82,165 @@ impl Parse for TraitFnLike {
     }
 }
 
-fn parse_proxy_impl(metas: &Vec<Meta>, item_impl: &ItemImpl) -> ProxyImpl {
+fn parse_proxy_impl(metas: &Vec<Meta>, item_impl: &ItemImpl) -> syn::Result<ProxyImpl> {
     let ItemImpl { attrs, items, .. } = item_impl;
 
     let (matched, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
-    if matches!(matched.len(), n if n > 0) {
-        panic!("so many proxy_impl 0");
-    }
+    check_no_nested(&matched, ATTR_PROXY_IMPL)?;
+    check_metas(metas, &[META_ACCESS_EXPR])?;
 
-    let impl_items: Vec<ProxyImplItem> = items
+    let impl_items = items
         .iter()
         .map(parse_proxy_impl_item)
-        .collect();
+        .collect::<syn::Result<Vec<_>>>()?;
 
     map_meta_to_local!(&metas => {
         META_ACCESS_EXPR => access_expr,
     });
 
-    ProxyImpl {
+    Ok(ProxyImpl {
         input: item_impl.clone(),
         reserved_attrs: surplus,
         items: impl_items,
-        access_expr: access_expr.as_ref().and_then(get_meta_value_as),
-    }
-}
-
-fn parse_proxy_impl_item(item: &ImplItem) -> ProxyImplItem {
-    match item {
-        ImplItem::Verbatim(payload) => {
-            let trait_item_like = parse::<TraitFnLike>(proc_macro::TokenStream::from(payload.to_token_stream()))
-                .expect("proxy_impl");
-
-            ProxyImplItem::TraitFnLike(trait_item_like)
-        }
-        ImplItem::Const(_) => unimplemented!("Impl item: ImplItem::Const"),
-        ImplItem::Fn(_) => unimplemented!("Impl item: ImplItem::Fn"),
-        ImplItem::Type(_) => unimplemented!("Impl item: ImplItem::Type"),
-        ImplItem::Macro(_) => unimplemented!("Impl item: ImplItem::Macro"),
-        _ => unimplemented!("Impl item: NonExhaustive"),
-    }
+        access_expr: access_expr.as_ref().map(get_meta_value_as).transpose()?,
+    })
 }
 
-fn quote_proxy_impl_item(body: &ProxyImpl, item: &ProxyImplItem) -> TokenStream {
-    let attrs = match item {
-        ProxyImplItem::TraitFnLike(TraitFnLike { attrs, .. }) => attrs,
-        _ => unimplemented!("on quote_proxy_impl_item"),
+fn parse_proxy_impl_item(item: &ImplItem) -> syn::Result<ProxyImplItem> {
+    let ImplItem::Verbatim(payload) = item else {
+        return Err(Error::new(item.span(), "proxy_impl expects fn signatures without a body, like `fn name(&self) -> T;`"));
     };
+    let trait_fn_like = parse2::<TraitFnLike>(payload.clone())?;
 
-    let (matched, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
+    let (matched, _) = separate_attr_by_name(&trait_fn_like.attrs, ATTR_INCLUDES);
+    let metas = get_metas_by_attr_name(&matched, ATTR_PROXY_IMPL)?;
+    check_metas(&metas, ITEM_METAS)?;
 
-    map_meta_to_local!(&get_metas_by_attr_name(&matched, ATTR_PROXY_IMPL) => {
+    map_meta_to_local!(&metas => {
         META_ACCESS_EXPR => access_expr,
         META_CONV_RETURN => conv_return,
+        META_SKIP_CONV_RETURN => skip_conv_return,
+    });
+
+    let args = trait_fn_like.sig.inputs.iter()
+        .filter_map(|arg| match arg {
+            FnArg::Receiver(_) => None,
+            FnArg::Typed(pat_type) => Some(parse_conf_arg(pat_type)),
+        })
+        .collect::<syn::Result<_>>()?;
+
+    let conf_item = ConfItem {
+        access_expr: access_expr.as_ref().map(get_meta_value_as).transpose()?,
+        conv_return: get_option_conf_usage(&conv_return)?,
+        skip_conv_return: skip_conv_return.is_some(),
+        args,
+    };
+    Ok(ProxyImplItem::TraitFnLike(trait_fn_like, conf_item))
+}
+
+fn parse_conf_arg(pat_type: &PatType) -> syn::Result<ConfArg> {
+    let PatType { attrs, pat, .. } = pat_type;
+
+    let (matched, _) = separate_attr_by_name(attrs, ATTR_INCLUDES);
+    let metas = get_metas_by_attr_name(&matched, ATTR_PROXY_IMPL)?;
+    check_metas(&metas, ARG_METAS)?;
+
+    map_meta_to_local!(&metas => {
+        META_CONV_ARG => conv_arg,
+        META_SKIP_CONV_ARG => skip_conv_arg,
     });
 
-    let conv_return = conv_return.as_ref().and_then(get_meta_value_as_conf_usage);
+    let Pat::Ident(PatIdent { ident: name, .. }) = pat.as_ref() else {
+        return Err(Error::new_spanned(pat, "proxy_impl arguments must be plain identifiers"));
+    };
+
+    Ok(ConfArg {
+        name: name.clone(),
+        conv_arg: get_option_conf_usage(&conv_arg)?,
+        skip_conv_arg: skip_conv_arg.is_some(),
+    })
+}
+
+fn quote_proxy_impl_item(body: &ProxyImpl, item: &ProxyImplItem) -> TokenStream {
+    let ProxyImplItem::TraitFnLike(TraitFnLike { vis, attrs, sig }, conf_item) = item;
+    let ConfItem { access_expr, conv_return, skip_conv_return, args } = conf_item;
+
+    let (_, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
 
-    let access_expr = access_expr.as_ref()
-        .and_then(get_meta_value_as)
+    let access_expr = access_expr.clone()
         .or(body.access_expr.clone())
-        .unwrap_or_else(|| parse_as::<Expr>(&"self.inner"));
-
-    match item {
-        ProxyImplItem::TraitFnLike(TraitFnLike { vis, attrs, sig }) => {
-            let vis = match vis {
-                None | Some(Visibility::Inherited) => &Visibility::Public(Pub::default()),
-                Some(vis) => vis,
-            };
-            let Signature { ident, inputs, output, .. } = sig;
-            let invoke_inputs = inputs.iter()
-                .filter(|input| !matches!(input, &FnArg::Receiver(_)))
-                .map(|arg| {
-                    match arg {
-                        FnArg::Receiver(receiver) => unimplemented!("FnArg::Receiver({})", receiver.to_token_stream().to_string()),
-                        FnArg::Typed(PatType { attrs, pat, ty, .. }) => {
-                            let (matched, _) = separate_attr_by_name(attrs, ATTR_INCLUDES);
-
-                            map_meta_to_local!(&get_metas_by_attr_name(&matched, ATTR_PROXY_IMPL) => {
-                                META_CONV_ARG => conv_arg,
-                                META_SKIP_CONV_ARG => skip_conv_arg,
-                            });
-
-                            let name = match pat.as_ref() {
-                                Pat::Ident(PatIdent { ident, .. }) => ident,
-                                _ => unimplemented!("on quote_proxy_impl_item"),
-                            };
-
-                            if skip_conv_arg.is_some() {
-                                return quote! { #name };
-                            }
-
-                            let conv_arg = conv_arg.and_then(|meta| get_meta_value_as_conf_usage(&meta));
-
-                            quote_option_conf_usage(name, &conv_arg)
-                        }
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            let stmt = quote! { #access_expr.#ident(#( #invoke_inputs ),*) };
-
-            let stmt = match output {
-                ReturnType::Default => stmt,
-                ReturnType::Type(_, _) => quote_option_conf_usage(&stmt, &conv_return),
-            };
-
-            let proxy_inputs = inputs.iter()
-                .map(|fn_arg| {
-                    match fn_arg {
-                        FnArg::Receiver(receiver) => {
-                            let (_, surplus) = separate_attr_by_name(&receiver.attrs, ATTR_INCLUDES);
-                            FnArg::Receiver(Receiver {
-                                attrs: surplus,
-                                ..receiver.clone()
-                            })
-                        }
-                        FnArg::Typed(pat_type) => {
-                            let (_, surplus) = separate_attr_by_name(&pat_type.attrs, ATTR_INCLUDES);
-                            FnArg::Typed(PatType {
-                                attrs: surplus,
-                                ..pat_type.clone()
-                            })
-                        }
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            let sig = Signature {
-                inputs: Punctuated::<FnArg, Token![,]>::from_iter(proxy_inputs),
-                ..sig.clone()
-            };
-
-            let napi_metas = Vec::<TokenStream>::new();
-
-            quote! {
-                #( #surplus )*
-                #[napi( #( #napi_metas )* )]
-                #vis #sig {
-                    #stmt
+        .unwrap_or_else(|| parse_quote!(self.inner));
+
+    let vis = match vis {
+        None | Some(Visibility::Inherited) => &Visibility::Public(Pub::default()),
+        Some(vis) => vis,
+    };
+    let Signature { ident, inputs, output, .. } = sig;
+    let invoke_inputs = args.iter()
+        .map(|ConfArg { name, conv_arg, skip_conv_arg }| {
+            if *skip_conv_arg {
+                return quote! { #name };
+            }
+            quote_option_conf_usage(name, conv_arg)
+        })
+        .collect::<Vec<_>>();
+
+    let stmt = quote! { #access_expr.#ident(#( #invoke_inputs ),*) };
+
+    let stmt = match output {
+        ReturnType::Type(_, _) if !*skip_conv_return => quote_option_conf_usage(&stmt, conv_return),
+        _ => stmt,
+    };
+
+    // a fallible access like `self.inner()?` turns every proxied fn into one returning `Result`
+    let (output, stmt) = match (&access_expr, output) {
+        (Expr::Try(_), ReturnType::Default) => (parse_quote!(-> napi::Result<()>), quote! { #stmt; Ok(()) }),
+        (Expr::Try(_), ReturnType::Type(_, ty)) if !is_result(ty) => (parse_quote!(-> napi::Result<#ty>), quote! { Ok(#stmt) }),
+        _ => (output.clone(), stmt),
+    };
+
+    let proxy_inputs = inputs.iter()
+        .map(|fn_arg| {
+            match fn_arg {
+                FnArg::Receiver(receiver) => {
+                    let (_, surplus) = separate_attr_by_name(&receiver.attrs, ATTR_INCLUDES);
+                    FnArg::Receiver(Receiver {
+                        attrs: surplus,
+                        ..receiver.clone()
+                    })
+                }
+                FnArg::Typed(pat_type) => {
+                    let (_, surplus) = separate_attr_by_name(&pat_type.attrs, ATTR_INCLUDES);
+                    FnArg::Typed(PatType {
+                        attrs: surplus,
+                        ..pat_type.clone()
+                    })
                 }
             }
+        })
+        .collect::<Vec<_>>();
+
+    let sig = Signature {
+        inputs: Punctuated::<FnArg, Token![,]>::from_iter(proxy_inputs),
+        output,
+        ..sig.clone()
+    };
+
+    let napi_metas = Vec::<TokenStream>::new();
+
+    quote! {
+        #( #surplus )*
+        #[napi( #( #napi_metas )* )]
+        #vis #sig {
+            #stmt
         }
-        _ => unimplemented!("on quote_proxy_impl_item"),
     }
 }
 
+fn is_result(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { path, .. }) if path.segments.last().is_some_and(|segment| segment.ident == "Result"))
+}
+
 impl ToTokens for ProxyImpl {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self { input, items, reserved_attrs, .. } = self;
@@ -1,8 +1,9 @@
 use crate::{
-    conf_convert::{parse_conf_convert, ConfConvert, NormalConfConvert},
+    conf_convert::{parse_conf_convert, ConfConvert, NormalConfConvert, CONVERT_METAS},
+    conf_serde::{parse_conf_serde, quote_serde_derive, quote_serde_rename, META_SERDE},
     conf_fields::{parse_conf_fields, quote_conf_fields, ConfField, ConfFields, Kind, With},
     conf_usage::quote_option_conf_usage,
-    utils::{append_to_tokens, get_type_ty_or, parse_metas, separate_attr_by_name},
+    utils::{append_to_tokens, check_metas, check_no_nested, get_type_ty_or, parse_metas, separate_attr_by_name},
 };
 use macros::{define_const_str, map_meta_to_local};
 use proc_macro2::{Ident, TokenStream};
@@ -10,13 +11,14 @@ use quote::{format_ident, quote, ToTokens};
 use syn::{parse_macro_input, Attribute, ItemStruct, Meta, Type};
 
 pub(crate) fn proxy_struct(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let metas = parse_metas(attrs);
-
     let item_struct = parse_macro_input!(input as ItemStruct);
 
-    let proxy_struct = parse_proxy_struct(&metas, &item_struct);
+    let proxy_struct = parse_metas(attrs).and_then(|metas| parse_proxy_struct(&metas, &item_struct));
 
-    proc_macro::TokenStream::from(quote! { #proxy_struct })
+    match proxy_struct {
+        Ok(proxy_struct) => proc_macro::TokenStream::from(quote! { #proxy_struct }),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
 
 define_const_str!(ATTR_PROXY_STRUCT = proxy_struct);
@@ -34,36 +36,37 @@ struct ProxyStruct {
     pub conf_fields: ConfFields,
     pub conf_convert: ConfConvert,
     pub object: bool,
+    pub serde: bool,
 }
 
-fn parse_proxy_struct(metas: &Vec<Meta>, item_struct: &ItemStruct) -> ProxyStruct {
+fn parse_proxy_struct(metas: &Vec<Meta>, item_struct: &ItemStruct) -> syn::Result<ProxyStruct> {
     let ItemStruct { attrs, ident, fields, .. } = item_struct;
 
     let (matched, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
-    if matches!(matched.len(), n if n > 0) {
-        panic!("so many proxy_struct");
-    }
+    check_no_nested(&matched, ATTR_PROXY_STRUCT)?;
+    check_metas(metas, &[&[META_OBJECT, META_ORIGIN_TYPE, META_SERDE], CONVERT_METAS].concat())?;
 
     map_meta_to_local!(&metas => {
         META_OBJECT => object,
         META_ORIGIN_TYPE => origin_type,
     });
 
-    ProxyStruct {
+    Ok(ProxyStruct {
         input: item_struct.clone(),
         reserved_attrs: surplus,
-        conf_fields: parse_conf_fields(fields, &matched, ATTR_PROXY_STRUCT, ATTR_INCLUDES),
-        origin_type: get_type_ty_or(&origin_type, &format_ident!("Origin{}", ident)),
+        conf_fields: parse_conf_fields(fields, &matched, ATTR_PROXY_STRUCT, ATTR_INCLUDES)?,
+        origin_type: get_type_ty_or(&origin_type, &format_ident!("Origin{}", ident))?,
         conf_convert: parse_conf_convert(metas),
         object: object.is_some(),
-    }
+        serde: parse_conf_serde(metas),
+    })
 }
 
 impl ToTokens for ProxyStruct {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             input, reserved_attrs, origin_type, conf_fields,
-            conf_convert, object,
+            conf_convert, object, serde,
         } = self;
 
         let ItemStruct { ident, vis, .. } = input;
@@ -75,8 +78,15 @@ impl ToTokens for ProxyStruct {
         if skip_to_js { napi_metas.push(quote! {object_to_js = false}) }
         if skip_from_js { napi_metas.push(quote! {object_from_js = false}) }
 
+        let serde_derive = serde.then(quote_serde_derive);
+        let fields = fields.iter().map(|field| {
+            let serde_rename = serde.then(|| quote_serde_rename(&field.field_ident));
+            quote! { #serde_rename #field }
+        });
+
         append_to_tokens(tokens, quote! {
             #[napi( #( #napi_metas ),* )]
+            #serde_derive
             #( #reserved_attrs )*
             #vis struct #ident {
                 #( #fields ),*
@@ -1,7 +1,11 @@
 use crate::{
-    conf_convert::{parse_conf_convert, ConfConvert, NormalConfConvert},
-    conf_usage::{get_meta_value_as_conf_usage, quote_option_conf_usage},
-    utils::{append_to_tokens, get_ident_optional, get_metas_by_attr_name, get_type_ty_or, parse_metas, separate_attr_by_name, to_case},
+    conf_convert::{parse_conf_convert, ConfConvert, NormalConfConvert, CONVERT_METAS},
+    conf_serde::{parse_conf_serde, META_SERDE},
+    conf_usage::{get_option_conf_usage, quote_option_conf_usage, ConfUsage},
+    utils::{
+        append_to_tokens, check_metas, check_no_nested, get_ident_optional, get_metas_by_attr_name,
+        get_type_ty_or, parse_metas, separate_attr_by_name, to_case,
+    },
 };
 use convert_case::Case;
 use macros::define_const_str;
@@ -10,13 +14,12 @@ use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{parse_macro_input, Attribute, Field, Ident, ItemStruct, LitStr, Meta, Type};
 
 pub(crate) fn proxy_wrap(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let metas = parse_metas(attrs);
-
     let macro_input = parse_macro_input!(input as ItemStruct);
 
-    let proxy_wrap = parse_proxy_wrap(&metas, &macro_input);
-
-    proc_macro::TokenStream::from(quote! { #proxy_wrap })
+    match parse_metas(attrs).and_then(|metas| parse_proxy_wrap(&metas, &macro_input)) {
+        Ok(proxy_wrap) => proc_macro::TokenStream::from(quote! { #proxy_wrap }),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
 
 macro_rules! map_meta_to_local {
@@ -43,6 +46,15 @@ define_const_str!(
     META_NO_SETTER = no_setter,
     META_CONV_SET = conv_set,
 );
+const FIELD_METAS: &[&str] = &[META_NO_GETTER, META_CONV_GET, META_GET_REF, META_NO_SETTER, META_CONV_SET];
+
+struct WrapField {
+    pub no_getter: bool,
+    pub conv_get: Option<ConfUsage>,
+    pub get_ref: bool,
+    pub no_setter: bool,
+    pub conv_set: Option<ConfUsage>,
+}
 
 struct ProxyWrap {
     pub input: ItemStruct,
@@ -54,15 +66,19 @@ struct ProxyWrap {
     pub no_setter: bool,
     pub use_non_null: bool,
     pub use_box: bool,
+    pub serde: bool,
+    pub wrap_fields: Vec<WrapField>,
 }
 
-fn parse_proxy_wrap(metas: &Vec<Meta>, item_struct: &ItemStruct) -> ProxyWrap {
-    let ItemStruct { attrs, ident, .. } = item_struct;
+fn parse_proxy_wrap(metas: &Vec<Meta>, item_struct: &ItemStruct) -> syn::Result<ProxyWrap> {
+    let ItemStruct { attrs, ident, fields, .. } = item_struct;
 
     let (matched, surplus) = separate_attr_by_name(attrs, ATTR_INCLUDES);
-    if matches!(matched.len(), n if n > 0) {
-        panic!("so many proxy_wrap");
-    }
+    check_no_nested(&matched, ATTR_PROXY_WRAP)?;
+    check_metas(metas, &[
+        &[META_ORIGIN_TYPE, META_FIELD_NAME, META_USE_NON_NULL, META_USE_BOX, META_NO_GETTER, META_NO_SETTER, META_SERDE],
+        CONVERT_METAS,
+    ].concat())?;
 
     map_meta_to_local!(&metas => {
         META_ORIGIN_TYPE => origin_type,
@@ -73,17 +89,41 @@ fn parse_proxy_wrap(metas: &Vec<Meta>, item_struct: &ItemStruct) -> ProxyWrap {
         META_NO_SETTER => no_setter,
     });
 
-    ProxyWrap {
+    Ok(ProxyWrap {
         input: item_struct.clone(),
         reserved_attrs: surplus,
-        origin_type: get_type_ty_or(&origin_type, &format_ident!("Origin{}", ident)),
-        field_name: get_ident_optional(&field_name),
+        origin_type: get_type_ty_or(&origin_type, &format_ident!("Origin{}", ident))?,
+        field_name: get_ident_optional(&field_name)?,
         conf_convert: parse_conf_convert(metas),
         no_getter: no_getter.is_some(),
         no_setter: no_setter.is_some(),
         use_non_null: use_non_null.is_some(),
         use_box: use_box.is_some(),
-    }
+        serde: parse_conf_serde(metas),
+        wrap_fields: fields.iter().map(parse_wrap_field).collect::<syn::Result<_>>()?,
+    })
+}
+
+fn parse_wrap_field(field: &Field) -> syn::Result<WrapField> {
+    let (matched, _) = separate_attr_by_name(&field.attrs, ATTR_INCLUDES);
+    let metas = get_metas_by_attr_name(&matched, ATTR_PROXY_WRAP)?;
+    check_metas(&metas, FIELD_METAS)?;
+
+    map_meta_to_local!(&metas => {
+        META_NO_GETTER => no_getter,
+        META_CONV_GET => conv_get,
+        META_GET_REF => get_ref,
+        META_NO_SETTER => no_setter,
+        META_CONV_SET => conv_set,
+    });
+
+    Ok(WrapField {
+        no_getter: no_getter.is_some(),
+        conv_get: get_option_conf_usage(&conv_get)?,
+        get_ref: get_ref.is_some(),
+        no_setter: no_setter.is_some(),
+        conv_set: get_option_conf_usage(&conv_set)?,
+    })
 }
 
 impl ToTokens for ProxyWrap {
@@ -91,7 +131,7 @@ impl ToTokens for ProxyWrap {
         let Self {
             input, reserved_attrs, origin_type, field_name,
             no_getter: root_no_getter, no_setter: root_no_setter, conf_convert,
-            use_non_null, use_box,
+            use_non_null, use_box, serde, wrap_fields,
         } = self;
 
         let ItemStruct { ident, vis, fields, .. } = input;
@@ -131,10 +171,14 @@ impl ToTokens for ProxyWrap {
             #vis struct #ident #wrap_body
         });
 
+        // the origin is opaque, only the fields with a getter are serialized, there is nothing to deserialize into
+        let mut serialized = Vec::new();
+
         if !fields.is_empty() {
-            let fields = fields.iter().zip(0..fields.len())
-                .map(|(field, fdx)| {
-                    let Field { attrs, ident: origin_ident, ty, .. } = field;
+            let fields = fields.iter().zip(wrap_fields).zip(0..fields.len())
+                .map(|((field, wrap_field), fdx)| {
+                    let Field { ident: origin_ident, ty, .. } = field;
+                    let WrapField { no_getter, conv_get, get_ref, no_setter, conv_set } = wrap_field;
 
                     let pat_pos = origin_ident.clone()
                         .map(|ident| quote! { #ident })
@@ -147,28 +191,18 @@ impl ToTokens for ProxyWrap {
 
                     let js_name = LitStr::new(&*js_name_string, ident.span());
 
-                    let (matched, _) = separate_attr_by_name(attrs, ATTR_INCLUDES);
-
-                    map_meta_to_local!(&get_metas_by_attr_name(&matched, ATTR_PROXY_WRAP) => {
-                        META_NO_GETTER => no_getter,
-                        META_CONV_GET => conv_get,
-                        META_GET_REF => get_ref,
-                        META_NO_SETTER => no_setter,
-                        META_CONV_SET => conv_set,
-                    });
-
                     let mut fns = TokenStream::default();
 
-                    let no_getter = *root_no_getter || no_getter.is_some();
-                    let no_setter = *root_no_setter || no_setter.is_some();
+                    let no_getter = *root_no_getter || *no_getter;
+                    let no_setter = *root_no_setter || *no_setter;
 
                     if !no_getter {
                         let getter = format_ident!("___get_{}", ident);
-                        let use_ref = get_ref.map(|_| { quote! { ref } });
-                        let conv_get = conv_get.as_ref().and_then(get_meta_value_as_conf_usage);
+                        serialized.push((js_name.clone(), getter.clone()));
+                        let use_ref = get_ref.then(|| quote! { ref });
 
                         let local_ident = quote! { val };
-                        let convert_code = quote_option_conf_usage(&local_ident, &conv_get);
+                        let convert_code = quote_option_conf_usage(&local_ident, conv_get);
                         append_to_tokens(&mut fns, quote_spanned! { ident.span() =>
                             #[napi(getter, js_name = #js_name)]
                             pub fn #getter (&self) -> #ty {
@@ -180,10 +214,9 @@ impl ToTokens for ProxyWrap {
 
                     if !no_setter {
                         let setter = format_ident!("___set_{}", ident);
-                        let conv_set = conv_set.as_ref().and_then(get_meta_value_as_conf_usage);
 
                         let local_ident = quote! { val };
-                        let convert_code = quote_option_conf_usage(&local_ident, &conv_set);
+                        let convert_code = quote_option_conf_usage(&local_ident, conv_set);
 
                         append_to_tokens(&mut fns, quote_spanned! { ident.span() =>
                             #[napi(setter, js_name = #js_name)]
@@ -204,6 +237,24 @@ impl ToTokens for ProxyWrap {
             });
         }
 
+        if *serde {
+            let name = LitStr::new(&ident.to_string(), ident.span());
+            let len = serialized.len();
+            let serialize_fields = serialized.iter()
+                .map(|(js_name, getter)| quote! { state.serialize_field(#js_name, &self.#getter())?; });
+
+            append_to_tokens(tokens, quote! {
+                impl serde::Serialize for #ident {
+                    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                        use serde::ser::SerializeStruct;
+                        let mut state = serializer.serialize_struct(#name, #len)?;
+                        #( #serialize_fields )*
+                        state.end()
+                    }
+                }
+            });
+        }
+
         if !skip_from_origin {
             let from_code = match &field_name {
                 Some(field) => quote! { #ident { #field: value } },
@@ -0,0 +1,76 @@
+use syn::{GenericArgument, PathArguments, Type, TypePath, TypeReference, TypeTuple};
+
+fn generic_args(path: &TypePath) -> Vec<&Type> {
+    let Some(segment) = path.path.segments.last() else { return vec![] };
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return vec![] };
+    args.args.iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect()
+}
+
+/// TypeScript type of a proxy field, `Option<T>` maps to `T` since napi marks the field itself optional
+pub(crate) fn field_ts_type(ty: &Type) -> String {
+    if let Type::Path(path) = ty
+        && let (Some(segment), [inner]) = (path.path.segments.last(), generic_args(path).as_slice())
+        && segment.ident == "Option"
+    {
+        return ts_type_of(inner);
+    }
+    ts_type_of(ty)
+}
+
+/// TypeScript type of a value as napi converts it
+pub(crate) fn ts_type_of(ty: &Type) -> String {
+    match ty {
+        Type::Reference(TypeReference { elem, .. }) => ts_type_of(elem),
+        Type::Tuple(TypeTuple { elems, .. }) if elems.is_empty() => "undefined".to_owned(),
+        Type::Tuple(TypeTuple { elems, .. }) => {
+            format!("[{}]", elems.iter().map(ts_type_of).collect::<Vec<_>>().join(", "))
+        }
+        Type::Path(path) => {
+            let Some(segment) = path.path.segments.last() else { return "unknown".to_owned() };
+            let args = generic_args(path);
+            match (segment.ident.to_string().as_str(), args.as_slice()) {
+                ("u8" | "u16" | "u32" | "i8" | "i16" | "i32" | "i64" | "f32" | "f64" | "usize" | "isize", _) => "number".to_owned(),
+                ("u64" | "i128" | "u128" | "BigInt", _) => "bigint".to_owned(),
+                ("bool", _) => "boolean".to_owned(),
+                ("String" | "str" | "char" | "SmolStr", _) => "string".to_owned(),
+                ("Option", [inner]) => format!("{} | null", ts_type_of(inner)),
+                ("Box" | "Rc" | "Arc", [inner]) => ts_type_of(inner),
+                ("Vec", [inner]) => format!("Array<{}>", ts_type_of(inner)),
+                ("HashMap" | "BTreeMap", [key, value]) => format!("Record<{}, {}>", ts_type_of(key), ts_type_of(value)),
+                (name, _) => name.to_owned(),
+            }
+        }
+        _ => "unknown".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{field_ts_type, ts_type_of};
+    use syn::{parse_quote, Type};
+
+    fn ts(ty: Type) -> String {
+        ts_type_of(&ty)
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(ts(parse_quote!(f64)), "number");
+        assert_eq!(ts(parse_quote!(u64)), "bigint");
+        assert_eq!(ts(parse_quote!(bool)), "boolean");
+        assert_eq!(ts(parse_quote!(std::string::String)), "string");
+    }
+
+    #[test]
+    fn test_wrappers() {
+        assert_eq!(field_ts_type(&parse_quote!(Option<Position>)), "Position");
+        assert_eq!(ts(parse_quote!(Vec<Option<u32>>)), "Array<number | null>");
+        assert_eq!(ts(parse_quote!((i32, String))), "[number, string]");
+        assert_eq!(ts(parse_quote!(&'static str)), "string");
+    }
+}
@@ -3,12 +3,15 @@ use proc_macro2::TokenStream;
 use quote::ToTokens;
 use syn::{
     parse::{Parse, ParseStream, Parser},
-    Attribute, Ident, Meta, MetaNameValue, Token, Type,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Error, Ident, Meta, MetaNameValue, Token, Type,
 };
 
 mod meta_getter;
 pub use meta_getter::*;
 
+/// Only for tokens built by the macros themselves, user input goes through `get_meta_value_as`
 pub(crate) fn parse_as<As: Parse>(to_tokens: &dyn ToTokens) -> As {
     try_parse2(to_tokens.to_token_stream(), As::parse)
         .expect(format!("failed to parse as {}", std::any::type_name::<As>()).as_str())
@@ -22,25 +25,73 @@ fn try_parse2<P: Parser>(tokens: TokenStream, p: P) -> syn::Result<P::Output> {
     Parser::parse2(p, tokens)
 }
 
-pub(crate) fn parse_metas(input: proc_macro::TokenStream) -> Vec<Meta> {
-    try_parse(input, parse_to_metas).unwrap_or_else(|_| vec![])
+pub(crate) fn parse_metas(input: proc_macro::TokenStream) -> syn::Result<Vec<Meta>> {
+    try_parse(input, parse_to_metas)
 }
 
 fn parse_to_metas(input: ParseStream) -> syn::Result<Vec<Meta>> {
-    let mut metas = Vec::<Meta>::new();
-    while let Ok(meta) = input.parse::<Meta>() {
-        input.parse::<Option<Token![,]>>()?;
-        metas.push(meta);
+    Ok(Punctuated::<Meta, Token![,]>::parse_terminated(input)?.into_iter().collect())
+}
+
+pub(crate) fn get_metas_by_attr_name(attrs: &Vec<Attribute>, name: &str) -> syn::Result<Vec<Meta>> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|attr| matches!(attr.path().get_ident(), Some(ident) if ident == name)) {
+        metas.extend(attr.parse_args_with(parse_to_metas)?);
     }
     Ok(metas)
 }
 
-pub(crate) fn get_metas_by_attr_name(attrs: &Vec<Attribute>, name: &str) -> Vec<Meta> {
-    attrs.iter()
-        .filter(|attr| matches!(attr.path().get_ident(), Some(ident) if ident == name))
-        .map(|attr| attr.parse_args_with(parse_to_metas).unwrap_or(vec![]))
-        .flatten()
-        .collect()
+fn meta_name(meta: &Meta) -> String {
+    meta.path().to_token_stream().to_string().replace(' ', "")
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = (prev + usize::from(ca != *cb)).min(row[j] + 1).min(current + 1);
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Every meta must be one of `known`, misspelled ones get a suggestion
+pub(crate) fn check_metas(metas: &[Meta], known: &[&str]) -> syn::Result<()> {
+    let errors = metas.iter()
+        .filter(|meta| !known.contains(&meta_name(meta).as_str()))
+        .map(|meta| {
+            let name = meta_name(meta);
+            let suggestion = known.iter()
+                .map(|candidate| (edit_distance(&name, candidate), candidate))
+                .filter(|(distance, _)| *distance <= 2)
+                .min_by_key(|(distance, _)| *distance);
+            let message = match suggestion {
+                Some((_, candidate)) => format!("unknown meta `{name}`, did you mean `{candidate}`?"),
+                None => format!("unknown meta `{name}`, expected one of: {}", known.join(", ")),
+            };
+            Error::new(meta.path().span(), message)
+        });
+    combine_errors(errors)
+}
+
+pub(crate) fn combine_errors(errors: impl IntoIterator<Item = Error>) -> syn::Result<()> {
+    match errors.into_iter().reduce(|mut all, error| { all.combine(error); all }) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// An attribute of the macro itself left on the item, e.g. `#[proxy_enum] #[proxy_enum]`
+pub(crate) fn check_no_nested(matched: &[Attribute], name: &str) -> syn::Result<()> {
+    match matched.first() {
+        Some(attr) => Err(Error::new_spanned(attr, format!("`{name}` is applied more than once"))),
+        None => Ok(()),
+    }
 }
 
 pub(crate) fn get_meta_by_name(metas: &Vec<Meta>, name: &str) -> Option<Meta> {
@@ -49,25 +100,27 @@ pub(crate) fn get_meta_by_name(metas: &Vec<Meta>, name: &str) -> Option<Meta> {
         .cloned()
 }
 
-pub(crate) fn get_meta_value_as<As: Parse>(meta: &Meta) -> Option<As> {
+pub(crate) fn get_meta_value_as<As: Parse>(meta: &Meta) -> syn::Result<As> {
     match meta {
-        Meta::NameValue(MetaNameValue { value, .. }) => Some(parse_as::<As>(value)),
-        _ => None,
+        Meta::NameValue(MetaNameValue { value, .. }) => {
+            try_parse2(value.to_token_stream(), As::parse).map_err(|e| Error::new_spanned(
+                value,
+                format!("invalid value for `{}`: {e}", meta_name(meta)),
+            ))
+        }
+        _ => Err(Error::new_spanned(meta, format!("expected `{} = ...`", meta_name(meta)))),
     }
 }
 
-pub(crate) fn get_type_ty_or<Input: ToTokens>(meta: &Option<Meta>, input: &Input) -> Type {
-    meta.as_ref()
-        .and_then(get_meta_value_as::<Type>)
-        .or_else(|| Some(parse_as::<Type>(input)))
-        .expect("cannot parse meta value as type")
+pub(crate) fn get_type_ty_or<Input: ToTokens>(meta: &Option<Meta>, input: &Input) -> syn::Result<Type> {
+    match meta {
+        Some(meta) => get_meta_value_as::<Type>(meta),
+        None => Ok(parse_as::<Type>(input)),
+    }
 }
 
-pub(crate) fn get_ident_optional(meta: &Option<Meta>) -> Option<Ident> {
-    let Some(meta) = meta else { return None };
-    let Some(ident) = get_meta_value_as(&meta)
-    else { panic!("cannot parse meta value as ident"); };
-    Some(ident)
+pub(crate) fn get_ident_optional(meta: &Option<Meta>) -> syn::Result<Option<Ident>> {
+    meta.as_ref().map(get_meta_value_as::<Ident>).transpose()
 }
 
 pub(crate) fn separate_attr_by_name(attrs: &Vec<Attribute>, names: &[&str]) -> (Vec<Attribute>, Vec<Attribute>) {
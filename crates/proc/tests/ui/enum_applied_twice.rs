@@ -0,0 +1,10 @@
+use proc::proxy_enum;
+
+#[proxy_enum(string_enum)]
+#[proxy_enum(string_enum)]
+enum Theme {
+    Light,
+    Dark,
+}
+
+fn main() {}
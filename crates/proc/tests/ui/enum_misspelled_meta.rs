@@ -0,0 +1,9 @@
+use proc::proxy_enum;
+
+#[proxy_enum(orign_type = u8, string_enum)]
+enum Theme {
+    Light,
+    Dark,
+}
+
+fn main() {}
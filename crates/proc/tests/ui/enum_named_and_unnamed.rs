@@ -0,0 +1,9 @@
+use proc::proxy_enum;
+
+#[proxy_enum(skip_backward)]
+enum Event {
+    #[proxy_enum(Named, Unnamed)]
+    Moved(f64),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use proc::proxy_enum;
+
+#[proxy_enum(skip_backward)]
+enum Event {
+    Moved {
+        #[proxy_enum(from = Into::into)]
+        x: f64,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use proc::proxy_flags;
+
+#[proxy_flags(origin = OriginButtons)]
+struct Buttons;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use proc::proxy_flags;
+
+#[proxy_flags(origin = OriginButtons, flags = [CLOSE, MINIMIZE])]
+struct Buttons;
+
+fn main() {}
@@ -0,0 +1,12 @@
+use proc::proxy_impl;
+
+struct Window;
+
+#[proxy_impl]
+impl Window {
+    fn title(&self) -> String {
+        String::new()
+    }
+}
+
+fn main() {}
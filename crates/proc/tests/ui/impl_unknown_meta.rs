@@ -0,0 +1,11 @@
+use proc::proxy_impl;
+
+struct Window;
+
+#[proxy_impl(access_expr = self.inner)]
+impl Window {
+    #[proxy_impl(conv_returns = option_into)]
+    fn title(&self) -> String;
+}
+
+fn main() {}
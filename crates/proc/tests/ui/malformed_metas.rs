@@ -0,0 +1,8 @@
+use proc::proxy_struct;
+
+#[proxy_struct(object origin_type = OriginSize)]
+struct Size {
+    width: u32,
+}
+
+fn main() {}
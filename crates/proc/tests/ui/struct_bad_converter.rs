@@ -0,0 +1,9 @@
+use proc::proxy_struct;
+
+#[proxy_struct(origin_type = OriginSize)]
+struct Size {
+    #[proxy_struct(from_origin = 42)]
+    width: u32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use proc::proxy_struct;
+
+#[proxy_struct(origin_type = 1 + 2)]
+struct Size {
+    width: u32,
+}
+
+fn main() {}
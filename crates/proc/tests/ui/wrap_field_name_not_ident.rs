@@ -0,0 +1,6 @@
+use proc::proxy_wrap;
+
+#[proxy_wrap(origin_type = OriginWindow, field_name = "inner")]
+struct Window;
+
+fn main() {}
@@ -0,0 +1,9 @@
+use proc::proxy_wrap;
+
+#[proxy_wrap(origin_type = OriginWindow, field_name = inner)]
+struct Window {
+    #[proxy_wrap(no_getters)]
+    id: u64,
+}
+
+fn main() {}
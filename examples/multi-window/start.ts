@@ -72,7 +72,10 @@ function createNewWindow(activeEventLoop: any) {
     return windowInfo;
 }
 
-function closeWindow(windowId: WindowId) {
+function closeWindow(activeEventLoop: any, windowId: WindowId) {
+    // hides the window and exits the loop once none are left (see setWindowRegistryOptions)
+    activeEventLoop.closeWindow(windowId);
+
     const windowIdString = windowId.rawString();
     const windowInfo = windows.get(windowIdString);
     if (windowInfo) {
@@ -89,6 +92,8 @@ function closeWindow(windowId: WindowId) {
 
 const app = Application.withSyncRef({
     onResumed: (eventLoop) => {
+        eventLoop.setWindowRegistryOptions({exitOnLastClose: true});
+
         // Create initial window
         createNewWindow(eventLoop);
         console.log('');
@@ -100,10 +105,7 @@ const app = Application.withSyncRef({
         if (!windowInfo) return;
 
         if (event.type === 'CloseRequested') {
-            closeWindow(windowId);
-            if (windows.size === 0) {
-                eventLoop.exit();
-            }
+            closeWindow(eventLoop, windowId);
             return;
         }
 
@@ -132,10 +134,8 @@ const app = Application.withSyncRef({
                     // C key closes current window
                     else if (ch === 'c') {
                         if (focusedWindowId) {
-                            closeWindow(focusedWindowId);
-                            if (windows.size === 0) {
-                                eventLoop.exit();
-                            }
+                            closeWindow(eventLoop, focusedWindowId);
+                            focusedWindowId = null;
                         }
                     }
                     // Number keys switch windows
@@ -1,2 +1,5 @@
 pub mod public;
+pub(crate) mod batch;
+pub(crate) mod middleware;
+pub(crate) mod swap;
 mod impl_cases;
\ No newline at end of file
@@ -0,0 +1,238 @@
+use std::cell::RefCell;
+
+use napi::bindgen_prelude::*;
+use winit::{
+    event::{DeviceEvent as OriginDeviceEvent, DeviceId as OriginDeviceId, ElementState, WindowEvent as OriginWindowEvent},
+    keyboard::{KeyLocation, PhysicalKey as OriginPhysicalKey},
+    window::WindowId as OriginWindowId,
+};
+
+use crate::{
+    event::{DeviceEvent, DeviceId, WindowEvent},
+    keyboard::PhysicalKey,
+    window::{registry, Window, WindowId},
+};
+
+/// How `onEvents` delivers the pointer and key streams
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct EventBatchOptions {
+    /// Move `CursorMoved` and `MouseMotion` into `PackedEvents.pointer`
+    pub pack_pointer: Option<bool>,
+    /// Move `KeyboardInput` into `PackedEvents.keys`
+    pub pack_keys: Option<bool>,
+}
+
+/// Window and device events of one loop iteration, in the order winit reported them
+#[napi(object_from_js = false)]
+pub enum QueuedEvent {
+    Window {
+        seq: u32,
+        window_id: WindowId,
+        event: WindowEvent,
+        #[napi(ts_type = "Window | null")]
+        window: Option<Reference<Window>>,
+    },
+    Device {
+        seq: u32,
+        device_id: DeviceId,
+        event: DeviceEvent,
+    },
+}
+
+/// Fixed size records, `seq` orders them against the `QueuedEvent`s of the same batch.
+///
+/// Window ids are `Number(windowId.raw())`, device events have `NaN` there.
+#[napi(object, object_from_js = false)]
+pub struct PackedEvents {
+    /// `[seq, kind, windowId, x, y]` per record, kind `0` is `CursorMoved` with the physical position,
+    /// kind `1` is `MouseMotion` with the raw delta
+    pub pointer: Float64Array,
+    /// `[seq, windowId, scancode, pressed, repeat, location, synthetic]` per record,
+    /// the scancode is `NaN` when unknown, see `physicalKeyFromScancode`
+    pub keys: Float64Array,
+}
+
+const POINTER_CURSOR_MOVED: f64 = 0.;
+const POINTER_MOUSE_MOTION: f64 = 1.;
+
+#[derive(Default)]
+struct Packed {
+    pointer: Vec<f64>,
+    keys: Vec<f64>,
+}
+
+impl Packed {
+    /// Records the event when it belongs to a packed stream
+    fn pack_window(&mut self, options: &EventBatchOptions, seq: u32, window_id: OriginWindowId, event: &OriginWindowEvent) -> bool {
+        let window_id = u64::from(window_id) as f64;
+        match event {
+            OriginWindowEvent::CursorMoved { position, .. } if options.pack_pointer.unwrap_or(false) => {
+                self.pointer.extend([seq as f64, POINTER_CURSOR_MOVED, window_id, position.x, position.y]);
+                true
+            }
+            OriginWindowEvent::KeyboardInput { event, is_synthetic, .. } if options.pack_keys.unwrap_or(false) => {
+                let location = match event.location {
+                    KeyLocation::Standard => 0.,
+                    KeyLocation::Left => 1.,
+                    KeyLocation::Right => 2.,
+                    KeyLocation::Numpad => 3.,
+                };
+                self.keys.extend([
+                    seq as f64,
+                    window_id,
+                    to_scancode(event.physical_key).map_or(f64::NAN, f64::from),
+                    f64::from(u8::from(event.state == ElementState::Pressed)),
+                    f64::from(u8::from(event.repeat)),
+                    location,
+                    f64::from(u8::from(*is_synthetic)),
+                ]);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn pack_device(&mut self, options: &EventBatchOptions, seq: u32, event: &OriginDeviceEvent) -> bool {
+        match event {
+            OriginDeviceEvent::MouseMotion { delta: (x, y) } if options.pack_pointer.unwrap_or(false) => {
+                self.pointer.extend([seq as f64, POINTER_MOUSE_MOTION, f64::NAN, *x, *y]);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Batch {
+    options: EventBatchOptions,
+    seq: u32,
+    events: Vec<QueuedEvent>,
+    packed: Packed,
+}
+
+thread_local! {
+    static BATCH: RefCell<Batch> = RefCell::new(Batch::default());
+}
+
+pub(crate) fn set_options(options: EventBatchOptions) {
+    BATCH.with_borrow_mut(|batch| batch.options = options);
+}
+
+/// Events that can't wait for the end of the iteration, the batch is delivered right after them:
+/// the `InnerSizeWriter` only works during the call, the registry closes windows after the call,
+/// and redraws are not always followed by `about_to_wait`
+pub(crate) fn flushes_now(event: &OriginWindowEvent) -> bool {
+    matches!(
+        event,
+        OriginWindowEvent::ScaleFactorChanged { .. }
+            | OriginWindowEvent::CloseRequested
+            | OriginWindowEvent::Destroyed
+            | OriginWindowEvent::RedrawRequested
+    )
+}
+
+pub(crate) fn push_window(window_id: OriginWindowId, event: OriginWindowEvent) {
+    BATCH.with_borrow_mut(|Batch { options, seq, events, packed }| {
+        let current = *seq;
+        *seq += 1;
+        if packed.pack_window(options, current, window_id, &event) {
+            return;
+        }
+        events.push(QueuedEvent::Window {
+            seq: current,
+            window_id: window_id.into(),
+            event: event.into(),
+            window: registry::get(window_id),
+        });
+    });
+}
+
+pub(crate) fn push_device(device_id: OriginDeviceId, event: OriginDeviceEvent) {
+    BATCH.with_borrow_mut(|Batch { options, seq, events, packed }| {
+        let current = *seq;
+        *seq += 1;
+        if packed.pack_device(options, current, &event) {
+            return;
+        }
+        events.push(QueuedEvent::Device { seq: current, device_id: device_id.into(), event: event.into() });
+    });
+}
+
+/// Everything queued since the last call, `None` when nothing was queued.
+///
+/// The packed streams are only present when one of the pack options is set.
+pub(crate) fn take() -> Option<(Vec<QueuedEvent>, Option<PackedEvents>)> {
+    BATCH.with_borrow_mut(|Batch { options, seq, events, packed }| {
+        if *seq == 0 {
+            return None;
+        }
+        *seq = 0;
+        let Packed { pointer, keys } = std::mem::take(packed);
+        let packing = options.pack_pointer.unwrap_or(false) || options.pack_keys.unwrap_or(false);
+        let packed = packing.then(|| PackedEvents { pointer: Float64Array::new(pointer), keys: Float64Array::new(keys) });
+        Some((std::mem::take(events), packed))
+    })
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+fn to_scancode(physical_key: OriginPhysicalKey) -> Option<u32> {
+    use winit::platform::scancode::PhysicalKeyExtScancode;
+    physical_key.to_scancode()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+fn to_scancode(_physical_key: OriginPhysicalKey) -> Option<u32> {
+    None
+}
+
+/// Physical key of a scancode from `PackedEvents.keys`
+#[napi]
+pub fn physical_key_from_scancode(scancode: u32) -> PhysicalKey {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    {
+        use winit::platform::scancode::PhysicalKeyExtScancode;
+        OriginPhysicalKey::from_scancode(scancode).into()
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+    {
+        OriginPhysicalKey::Unidentified(winit::keyboard::NativeKeyCode::Unidentified).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{
+        dpi::PhysicalPosition,
+        event::{DeviceEvent, DeviceId, WindowEvent},
+        window::WindowId,
+    };
+    use super::{EventBatchOptions, Packed};
+
+    #[test]
+    fn test_pack_pointer() {
+        let options = EventBatchOptions { pack_pointer: Some(true), pack_keys: None };
+        let mut packed = Packed::default();
+        let window_id = WindowId::from(7);
+        let moved = WindowEvent::CursorMoved { device_id: DeviceId::dummy(), position: PhysicalPosition::new(1., 2.) };
+
+        assert!(packed.pack_window(&options, 0, window_id, &moved));
+        assert!(!packed.pack_window(&options, 1, window_id, &WindowEvent::Focused(true)));
+        assert!(packed.pack_device(&options, 2, &DeviceEvent::MouseMotion { delta: (3., 4.) }));
+
+        assert_eq!(packed.pointer.len(), 10);
+        assert_eq!(packed.pointer[..5], [0., 0., 7., 1., 2.]);
+        assert_eq!(packed.pointer[5..7], [2., 1.]);
+        assert!(packed.pointer[7].is_nan());
+        assert_eq!(packed.pointer[8..], [3., 4.]);
+    }
+
+    #[test]
+    fn test_pack_disabled() {
+        let mut packed = Packed::default();
+        let moved = WindowEvent::CursorMoved { device_id: DeviceId::dummy(), position: PhysicalPosition::new(1., 2.) };
+        assert!(!packed.pack_window(&EventBatchOptions::default(), 0, WindowId::from(7), &moved));
+        assert!(packed.pointer.is_empty());
+    }
+}
@@ -5,64 +5,134 @@ use crate::{
     application::public::OptionsFxHolder,
     application::public::OptionsSafeHolder,
     application::public::{OptionsRefHolder, OptionsGhostHolder},
-    event::UserPayload,
+    application::batch,
+    application::middleware::{self, ToJsArg},
+    event::{DragDropEvent, LazyWindowEvent, UserPayload},
+    window::{self, drag_drop, registry},
+    utils::scope::HandlerScope,
     handle_res,
     handle_rop,
 };
 
 macro_rules! impl_with_call_macro {
-    (impl <$($life: lifetime), *> $user_event: ty => $impl_ty: ty | $get_macro: ident + $call_macro: ident) => {
+    (impl <$($life: lifetime), *> $user_event: ty => $impl_ty: ty | $get_macro: ident + $call_macro: ident, $enter: ident) => {
         impl <$($life), *> winit::application::ApplicationHandler<$user_event> for $impl_ty {
             fn new_events(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, cause: winit::event::StartCause) {
+                let _scope = HandlerScope::$enter();
                 $get_macro!($call_macro, self, on_new_events?, event_loop, cause);
             }
         
             fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+                let _scope = HandlerScope::$enter();
+                self.flush_events(event_loop);
                 $get_macro!($call_macro, self, on_resumed, event_loop);
             }
         
             fn user_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, event: $user_event) {
+                let _scope = HandlerScope::$enter();
+                self.flush_events(event_loop);
                 $get_macro!($call_macro, self, on_user_event?, event_loop, event);
             }
         
-            fn window_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId, event: winit::event::WindowEvent) {
-                $get_macro!($call_macro, self, on_window_event, event_loop, window_id, event);
+            fn window_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId, mut event: winit::event::WindowEvent) {
+                let _scope = HandlerScope::$enter();
+                window::before_window_event(window_id, &mut event);
+                // runs on drop, the call macros may return early
+                let _after = registry::AfterWindowEvent::new(event_loop, window_id, &event);
+                if self.batching() {
+                    let flush = batch::flushes_now(&event);
+                    batch::push_window(window_id, event);
+                    if flush {
+                        self.flush_events(event_loop);
+                    }
+                    return;
+                }
+                let window = registry::get(window_id);
+                if self.lazy_window_events() {
+                    $get_macro!($call_macro, self, on_lazy_window_event?, event_loop, window_id, LazyWindowEvent::from(event), window);
+                    return;
+                }
+                $get_macro!($call_macro, self, on_window_event?, event_loop, window_id, event, window);
             }
         
             fn device_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, device_id: winit::event::DeviceId, event: winit::event::DeviceEvent) {
+                let _scope = HandlerScope::$enter();
+                if self.batching() {
+                    batch::push_device(device_id, event);
+                    return;
+                }
                 $get_macro!($call_macro, self, on_device_event?, event_loop, device_id, event);
             }
         
             fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+                let _scope = HandlerScope::$enter();
+                self.flush_events(event_loop);
+                for (window_id, event) in drag_drop::take_events() {
+                    self.drag_drop(event_loop, window_id, event);
+                }
                 $get_macro!($call_macro, self, on_about_to_wait?, event_loop);
             }
         
             fn suspended(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+                let _scope = HandlerScope::$enter();
+                self.flush_events(event_loop);
                 $get_macro!($call_macro, self, on_suspended?, event_loop);
             }
         
             fn exiting(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+                let _scope = HandlerScope::$enter();
+                self.flush_events(event_loop);
                 $get_macro!($call_macro, self, on_exiting?, event_loop);
             }
         
             fn memory_warning(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+                let _scope = HandlerScope::$enter();
+                self.flush_events(event_loop);
                 $get_macro!($call_macro, self, on_memory_warning?, event_loop);
             }
         }
+
+        impl <$($life), *> $impl_ty {
+            // own fn, the call macros return early
+            fn drag_drop(&self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId, event: DragDropEvent) {
+                let window = registry::get(window_id);
+                $get_macro!($call_macro, self, on_drag_drop?, event_loop, window_id, event, window);
+            }
+
+            fn flush_events(&self, event_loop: &winit::event_loop::ActiveEventLoop) {
+                let Some((events, packed)) = batch::take() else { return };
+                $get_macro!($call_macro, self, on_events?, event_loop, events, packed);
+            }
+        }
     };
 }
 
 macro_rules! get_direct {
     ($call_macro: ident, $self: ident, $func: ident, $($args: expr), +) => {
         let Self { $func: $func, .. } = &$self;
+        dispatch_layers!($func, Some($func), $($args), +);
         $call_macro!($func $(, $args)+);
     };
     ($call_macro: ident, $self: ident, $func: ident?, $($args: expr), +) => {
-        let Self { $func: Some($func), .. } = &$self else { return; };
+        let Self { $func: $func, .. } = &$self;
+        dispatch_layers!($func, $func.as_ref(), $($args), +);
+        let Some($func) = $func else { return; };
         $call_macro!($func $(, $args)+);
     };
 }
 
+// with layers in use the arguments are converted once here and the hooks are called raw
+macro_rules! dispatch_layers {
+    ($func: ident, $base: expr, $($args: expr), +) => {
+        if middleware::active() {
+            middleware::dispatch(stringify!($func), $base, |env| {
+                Ok(vec![$(ToJsArg::to_js_arg($args, env)?), +])
+            });
+            return;
+        }
+    };
+}
+
 macro_rules! call_fx_sync {
     ($fx: ident, $($args: expr), +) => {
         let result = $fx.call(FnArgs::from(($(From::from($args), )+)));
@@ -77,17 +147,20 @@ macro_rules! call_fx_async {
     }
 }
 
-impl_with_call_macro!(impl <'scope> UserPayload => OptionsFxHolder<'scope, Unknown<'scope>> | get_direct + call_fx_sync);
+impl_with_call_macro!(impl <'scope> UserPayload => OptionsFxHolder<'scope, Unknown<'scope>> | get_direct + call_fx_sync, enter);
 
-impl_with_call_macro!(impl <'scope> UserPayload => OptionsFxHolder<'scope, Option<Promise<()>>> | get_direct + call_fx_async);
+impl_with_call_macro!(impl <'scope> UserPayload => OptionsFxHolder<'scope, Option<Promise<()>>> | get_direct + call_fx_async, enter);
 
 macro_rules! get_with_env {
     ($call_macro: ident, $self: ident, $func: ident, $($args: expr), +) => {
         let Self { env, options: OptionsGhostHolder { $func: $func, .. } } = &$self;
+        dispatch_layers!($func, Some($func), $($args), +);
         $call_macro!($func@env $(, $args)+);
     };
     ($call_macro: ident, $self: ident, $func: ident?, $($args: expr), +) => {
-        let Self { env, options: OptionsGhostHolder { $func: Some($func), .. } } = &$self else { return; };
+        let Self { env, options: OptionsGhostHolder { $func: $func, .. } } = &$self;
+        dispatch_layers!($func, $func.as_ref(), $($args), +);
+        let Some($func) = $func else { return; };
         $call_macro!($func@env $(, $args)+);
     };
 }
@@ -108,9 +181,9 @@ macro_rules! call_ref_async {
     }
 }
 
-impl_with_call_macro!(impl <'scope> UserPayload => OptionsRefHolder<Unknown<'scope>> | get_with_env + call_ref_sync);
+impl_with_call_macro!(impl <'scope> UserPayload => OptionsRefHolder<Unknown<'scope>> | get_with_env + call_ref_sync, enter);
 
-impl_with_call_macro!(impl <> UserPayload => OptionsRefHolder<Option<Promise<()>>> | get_with_env + call_ref_async);
+impl_with_call_macro!(impl <> UserPayload => OptionsRefHolder<Option<Promise<()>>> | get_with_env + call_ref_async, enter);
 
 macro_rules! call_tsfn {
     ($fx: ident, $($args: expr), +) => {
@@ -119,4 +192,17 @@ macro_rules! call_tsfn {
     }
 }
 
-impl_with_call_macro!(impl <> UserPayload => OptionsSafeHolder<Option<Promise<()>>> | get_direct + call_tsfn);
\ No newline at end of file
+// threadsafe handlers run off the js thread, `Application.use` is refused for them and what they get
+// is stamped to last as long as the event loop
+macro_rules! get_threadsafe {
+    ($call_macro: ident, $self: ident, $func: ident, $($args: expr), +) => {
+        let Self { $func: $func, .. } = &$self;
+        $call_macro!($func $(, $args)+);
+    };
+    ($call_macro: ident, $self: ident, $func: ident?, $($args: expr), +) => {
+        let Self { $func: Some($func), .. } = &$self else { return; };
+        $call_macro!($func $(, $args)+);
+    };
+}
+
+impl_with_call_macro!(impl <> UserPayload => OptionsSafeHolder<Option<Promise<()>>> | get_threadsafe + call_tsfn, enter_deferred);
\ No newline at end of file
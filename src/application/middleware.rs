@@ -0,0 +1,324 @@
+use std::{cell::RefCell, rc::Rc};
+
+use napi::{bindgen_prelude::*, sys, JsValue, ValueType};
+
+use crate::{
+    application::batch::{PackedEvents, QueuedEvent},
+    event::{DeviceEvent, DeviceId, DragDropEvent, LazyWindowEvent, StartCause, UserPayload, WindowEvent},
+    event_loop::ActiveEventLoop,
+    handle_res,
+    handle_rop,
+    napi_reason,
+    window::{Window, WindowId},
+};
+
+/// Hooks of a layer, each one is called before the hook of the same name on the `Application`.
+///
+/// Return `false` to swallow the event, a value to replace the event for the layers after it and the
+/// application, anything else forwards it unchanged. Promises returned by layers are not awaited, the
+/// application hooks are handled as without layers.
+#[napi(object, object_to_js = false)]
+pub struct ApplicationLayer<'env> {
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, cause: StartCause) => (boolean | StartCause | void)")]
+    pub on_new_events: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (boolean | void)")]
+    pub on_resumed: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, event: UserPayload) => (boolean | UserPayload | void)")]
+    pub on_user_event: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: WindowEvent, window: Window | null) => (boolean | WindowEvent | void)")]
+    pub on_window_event: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: LazyWindowEvent, window: Window | null) => (boolean | void)")]
+    pub on_lazy_window_event: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, deviceId: DeviceId, event: DeviceEvent) => (boolean | DeviceEvent | void)")]
+    pub on_device_event: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, events: QueuedEvent[], packed: PackedEvents | null) => (boolean | QueuedEvent[] | void)")]
+    pub on_events: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: DragDropEvent, window: Window | null) => (boolean | DragDropEvent | void)")]
+    pub on_drag_drop: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (boolean | void)")]
+    pub on_about_to_wait: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (boolean | void)")]
+    pub on_suspended: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (boolean | void)")]
+    pub on_exiting: Option<Unknown<'env>>,
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (boolean | void)")]
+    pub on_memory_warning: Option<Unknown<'env>>,
+}
+
+struct Layer {
+    id: u32,
+    order: i32,
+    object: ObjectRef,
+}
+
+struct Chain {
+    env: Env,
+    next_id: u32,
+    layers: Vec<Layer>,
+}
+
+impl Drop for Chain {
+    fn drop(&mut self) {
+        for layer in self.layers.drain(..) {
+            let _ = layer.object.unref(&self.env);
+        }
+    }
+}
+
+/// Layers of one `Application`, shared with the `LayerHandle`s
+#[derive(Clone)]
+pub(crate) struct Layers(Rc<RefCell<Chain>>);
+
+impl Layers {
+    pub(crate) fn new(env: Env) -> Self {
+        Self(Rc::new(RefCell::new(Chain { env, next_id: 0, layers: Vec::new() })))
+    }
+
+    pub(crate) fn add(&self, layer: Unknown, order: i32) -> Result<LayerHandle> {
+        if layer.get_type()? != ValueType::Object {
+            return Err(napi_reason!("a layer must be an object with hooks, like `{{ onWindowEvent() {{}} }}`"));
+        }
+        let object = unsafe { layer.cast::<Object>()? }.create_ref()?;
+
+        let mut chain = self.0.borrow_mut();
+        let id = chain.next_id;
+        chain.next_id += 1;
+        // stable, layers of the same order run in the order they were added
+        let at = chain.layers.partition_point(|layer| layer.order <= order);
+        chain.layers.insert(at, Layer { id, order, object });
+
+        Ok(LayerHandle { layers: self.clone(), id })
+    }
+
+    fn remove(&self, id: u32) -> Result<bool> {
+        let mut chain = self.0.borrow_mut();
+        let Some(at) = chain.layers.iter().position(|layer| layer.id == id) else { return Ok(false) };
+        let layer = chain.layers.remove(at);
+        layer.object.unref(&chain.env)?;
+        Ok(true)
+    }
+
+    fn contains(&self, id: u32) -> bool {
+        self.0.borrow().layers.iter().any(|layer| layer.id == id)
+    }
+}
+
+/// Returned by `Application.use`
+#[napi]
+pub struct LayerHandle {
+    layers: Layers,
+    id: u32,
+}
+
+#[napi]
+impl LayerHandle {
+    /// Takes the layer out of the chain, `false` when it was already removed
+    #[napi]
+    pub fn remove(&self) -> Result<bool> {
+        self.layers.remove(self.id)
+    }
+
+    #[napi(getter)]
+    pub fn attached(&self) -> bool {
+        self.layers.contains(self.id)
+    }
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Layers>> = const { RefCell::new(None) };
+}
+
+/// Makes the layers of an `Application` visible to its handlers while the event loop runs it
+pub(crate) struct Installed {
+    previous: Option<Layers>,
+}
+
+impl Installed {
+    pub(crate) fn new(layers: &Layers) -> Self {
+        Self { previous: ACTIVE.replace(Some(layers.clone())) }
+    }
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        ACTIVE.set(self.previous.take());
+    }
+}
+
+#[inline]
+pub(crate) fn active() -> bool {
+    ACTIVE.with_borrow(|layers| layers.as_ref().is_some_and(|layers| !layers.0.borrow().layers.is_empty()))
+}
+
+/// What the runner does with the result of an application hook, the same with or without layers
+pub(crate) trait Settle: FromNapiValue {
+    fn settle(result: Result<Option<Self>>);
+}
+
+impl Settle for Unknown<'_> {
+    fn settle(result: Result<Option<Self>>) {
+        handle_res!(result);
+    }
+}
+
+impl Settle for Option<Promise<()>> {
+    fn settle(result: Result<Option<Self>>) {
+        let result = result.map(Option::flatten);
+        handle_rop!(spawn(Some(promise) @ result));
+    }
+}
+
+/// The js function behind an application hook
+pub(crate) trait RawHook {
+    type Return: Settle;
+    fn call_raw(&self, env: &Env, argv: &Argv) -> Result<Self::Return>;
+}
+
+impl<Args: JsValuesTupleIntoVec, Return: Settle> RawHook for Function<'_, Args, Return> {
+    type Return = Return;
+    fn call_raw(&self, _env: &Env, argv: &Argv) -> Result<Return> {
+        call_with(self.to_unknown(), argv)
+    }
+}
+
+impl<Args: JsValuesTupleIntoVec, Return: Settle> RawHook for FunctionRef<Args, Return> {
+    type Return = Return;
+    fn call_raw(&self, env: &Env, argv: &Argv) -> Result<Return> {
+        call_with(self.borrow_back(env)?.to_unknown(), argv)
+    }
+}
+
+/// Calls a function with the shared arguments instead of the ones it was typed with
+fn call_with<Return: FromNapiValue>(function: Unknown, argv: &Argv) -> Result<Return> {
+    // only called with values known to be functions
+    let function = unsafe { function.cast::<Function<&Argv, Return>>()? };
+    function.call(argv)
+}
+
+/// Hook arguments, converted once and shared by every layer
+pub(crate) struct Argv<'env>(Vec<Unknown<'env>>);
+
+impl JsValuesTupleIntoVec for &Argv<'_> {
+    fn into_vec(self, _env: sys::napi_env) -> Result<Vec<sys::napi_value>> {
+        Ok(self.0.iter().map(JsValue::raw).collect())
+    }
+}
+
+pub(crate) trait ToJsArg {
+    fn to_js_arg(self, env: &Env) -> Result<Unknown<'_>>;
+}
+
+macro_rules! impl_to_js_arg {
+    ($($origin: ty => $proxy: ty), * $(,)?) => {
+        $(
+            impl ToJsArg for $origin {
+                fn to_js_arg(self, env: &Env) -> Result<Unknown<'_>> {
+                    <$proxy>::from(self).into_unknown(env)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_js_arg!(
+    &winit::event_loop::ActiveEventLoop => ActiveEventLoop,
+    winit::event::StartCause => StartCause,
+    winit::window::WindowId => WindowId,
+    winit::event::WindowEvent => WindowEvent,
+    winit::event::DeviceId => DeviceId,
+    winit::event::DeviceEvent => DeviceEvent,
+    UserPayload => UserPayload,
+    Option<Reference<Window>> => Option<Reference<Window>>,
+    DragDropEvent => DragDropEvent,
+    LazyWindowEvent => LazyWindowEvent,
+    Vec<QueuedEvent> => Vec<QueuedEvent>,
+    Option<PackedEvents> => Option<PackedEvents>,
+);
+
+/// Position of the argument a layer may replace
+fn payload_index(hook: &str) -> Option<usize> {
+    match hook {
+        "on_new_events" | "on_user_event" | "on_events" => Some(1),
+        "on_window_event" | "on_device_event" | "on_drag_drop" => Some(2),
+        _ => None,
+    }
+}
+
+/// `on_window_event` to `onWindowEvent`
+fn js_hook_name(hook: &str) -> String {
+    let mut name = String::with_capacity(hook.len());
+    let mut upper = false;
+    for c in hook.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                name.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+/// Runs the hook through the active layers, then through the application hook if no layer swallowed it
+pub(crate) fn dispatch<H: RawHook>(hook: &'static str, base: Option<&H>, args: impl FnOnce(&Env) -> Result<Vec<Unknown<'_>>>) {
+    let Some(layers) = ACTIVE.with_borrow(Clone::clone) else { return };
+    H::Return::settle(run(&layers, hook, base, args));
+}
+
+/// `None` when a layer swallowed the event or the application has no such hook
+fn run<H: RawHook>(layers: &Layers, hook: &'static str, base: Option<&H>, args: impl FnOnce(&Env) -> Result<Vec<Unknown<'_>>>) -> Result<Option<H::Return>> {
+    let env = layers.0.borrow().env;
+    // resolved up front, hooks may add or remove layers while they run
+    let objects = layers.0.borrow().layers.iter()
+        .map(|layer| layer.object.get_value(&env))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut argv = Argv(args(&env)?);
+    let name = js_hook_name(hook);
+
+    for object in objects {
+        let func = object.get_named_property_unchecked::<Unknown>(&name)?;
+        if func.get_type()? != ValueType::Function {
+            continue;
+        }
+
+        // bound, `apply` would leave a thrown error pending
+        let func = unsafe { func.cast::<Function<&Argv, Unknown>>()? };
+        let result = func.bind(object)?.call(&argv)?;
+        match result.get_type()? {
+            ValueType::Undefined | ValueType::Null => {}
+            ValueType::Boolean => {
+                if !unsafe { result.cast::<bool>()? } {
+                    return Ok(None);
+                }
+            }
+            _ if result.is_promise()? => {}
+            _ => {
+                if let Some(index) = payload_index(hook) {
+                    argv.0[index] = result;
+                }
+            }
+        }
+    }
+
+    base.map(|base| base.call_raw(&env, &argv)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{js_hook_name, payload_index};
+
+    #[test]
+    fn test_js_hook_name() {
+        assert_eq!(js_hook_name("on_window_event"), "onWindowEvent");
+        assert_eq!(js_hook_name("on_resumed"), "onResumed");
+    }
+
+    #[test]
+    fn test_payload_index() {
+        assert_eq!(payload_index("on_window_event"), Some(2));
+        assert_eq!(payload_index("on_about_to_wait"), None);
+    }
+}
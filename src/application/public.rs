@@ -5,16 +5,27 @@ use crate::{
     event::{
         DeviceEvent,
         DeviceId,
+        DragDropEvent,
+        LazyWindowEvent,
         StartCause,
         UserPayload,
         WindowEvent,
     },
+    application::batch::{PackedEvents, QueuedEvent},
+    application::middleware::{LayerHandle, Layers},
+    application::swap::PendingHandlers,
     event_loop::ActiveEventLoop,
     window::WindowId,
     utils::alias::ThreadsafeNoCallee,
+    napi_reason,
 };
 use crate::window::Window;
 
+pub(crate) type WindowEventArgs = FnArgs<(ActiveEventLoop, WindowId, WindowEvent, Option<Reference<Window>>)>;
+pub(crate) type LazyWindowEventArgs = FnArgs<(ActiveEventLoop, WindowId, LazyWindowEvent, Option<Reference<Window>>)>;
+pub(crate) type DragDropArgs = FnArgs<(ActiveEventLoop, WindowId, DragDropEvent, Option<Reference<Window>>)>;
+pub(crate) type EventsArgs = FnArgs<(ActiveEventLoop, Vec<QueuedEvent>, Option<PackedEvents>)>;
+
 #[napi(object, object_to_js = false)]
 pub struct ApplicationOptions<'env> {
     #[napi(ts_type = "(eventLoop: ActiveEventLoop, cause: StartCause) => (void | Promise<void>)")]
@@ -23,10 +34,24 @@ pub struct ApplicationOptions<'env> {
     pub on_resumed: Unknown<'env>,
     #[napi(ts_type = "(eventLoop: ActiveEventLoop, event: UserPayload) => (void | Promise<void>)")]
     pub on_user_event: Option<Unknown<'env>>,
-    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: WindowEvent) => (void | Promise<void>)")]
-    pub on_window_event: Unknown<'env>,
+    /// Can be left out when `onEvents` is given
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: WindowEvent, window: Window | null) => (void | Promise<void>)")]
+    pub on_window_event: Option<Unknown<'env>>,
+    /// Takes the place of `onWindowEvent`, the event converts only when `event.event` is read
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: LazyWindowEvent, window: Window | null) => (void | Promise<void>)")]
+    pub on_lazy_window_event: Option<Unknown<'env>>,
     #[napi(ts_type = "(eventLoop: ActiveEventLoop, deviceId: DeviceId, event: DeviceEvent) => (void | Promise<void>)")]
     pub on_device_event: Option<Unknown<'env>>,
+    /// Batch mode, window and device events of a loop iteration are queued and delivered in one call
+    /// before `onAboutToWait` instead of going to `onWindowEvent` and `onDeviceEvent`.
+    ///
+    /// `ScaleFactorChanged`, `CloseRequested`, `Destroyed` and `RedrawRequested` deliver the batch right away,
+    /// `ActiveEventLoop.setEventBatchOptions` moves pointer and key streams into typed arrays
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, events: QueuedEvent[], packed: PackedEvents | null) => (void | Promise<void>)")]
+    pub on_events: Option<Unknown<'env>>,
+    /// Grouped drag and drop events, delivered right before `onAboutToWait`
+    #[napi(ts_type = "(eventLoop: ActiveEventLoop, windowId: WindowId, event: DragDropEvent, window: Window | null) => (void | Promise<void>)")]
+    pub on_drag_drop: Option<Unknown<'env>>,
     #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (void | Promise<void>)")]
     pub on_about_to_wait: Option<Unknown<'env>>,
     #[napi(ts_type = "(eventLoop: ActiveEventLoop) => (void | Promise<void>)")]
@@ -42,8 +67,11 @@ pub struct ApplicationOptionsFxAsync<'scope> {
     pub on_new_events: Option<Function<'scope, FnArgs<(ActiveEventLoop, StartCause)>, Option<Promise<()>>>>,
     pub on_resumed: Function<'scope, FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>,
     pub on_user_event: Option<Function<'scope, FnArgs<(ActiveEventLoop, UserPayload)>, Option<Promise<()>>>>,
-    pub on_window_event: Function<'scope, FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Option<Promise<()>>>,
+    pub on_window_event: Option<Function<'scope, WindowEventArgs, Option<Promise<()>>>>,
+    pub on_lazy_window_event: Option<Function<'scope, LazyWindowEventArgs, Option<Promise<()>>>>,
     pub on_device_event: Option<Function<'scope, FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Option<Promise<()>>>>,
+    pub on_events: Option<Function<'scope, EventsArgs, Option<Promise<()>>>>,
+    pub on_drag_drop: Option<Function<'scope, DragDropArgs, Option<Promise<()>>>>,
     pub on_about_to_wait: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>>,
     pub on_suspended: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>>,
     pub on_exiting: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>>,
@@ -55,8 +83,11 @@ pub struct ApplicationOptionsFxSync<'scope> {
     pub on_new_events: Option<Function<'scope, FnArgs<(ActiveEventLoop, StartCause)>, Unknown<'scope>>>,
     pub on_resumed: Function<'scope, FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>,
     pub on_user_event: Option<Function<'scope, FnArgs<(ActiveEventLoop, UserPayload)>, Unknown<'scope>>>,
-    pub on_window_event: Function<'scope, FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Unknown<'scope>>,
+    pub on_window_event: Option<Function<'scope, WindowEventArgs, Unknown<'scope>>>,
+    pub on_lazy_window_event: Option<Function<'scope, LazyWindowEventArgs, Unknown<'scope>>>,
     pub on_device_event: Option<Function<'scope, FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Unknown<'scope>>>,
+    pub on_events: Option<Function<'scope, EventsArgs, Unknown<'scope>>>,
+    pub on_drag_drop: Option<Function<'scope, DragDropArgs, Unknown<'scope>>>,
     pub on_about_to_wait: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>>,
     pub on_suspended: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>>,
     pub on_exiting: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>>,
@@ -68,8 +99,11 @@ pub struct ApplicationOptionsRefAsync {
     pub on_new_events: Option<FunctionRef<FnArgs<(ActiveEventLoop, StartCause)>, Option<Promise<()>>>>,
     pub on_resumed: FunctionRef<FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>,
     pub on_user_event: Option<FunctionRef<FnArgs<(ActiveEventLoop, UserPayload)>, Option<Promise<()>>>>,
-    pub on_window_event: FunctionRef<FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Option<Promise<()>>>,
+    pub on_window_event: Option<FunctionRef<WindowEventArgs, Option<Promise<()>>>>,
+    pub on_lazy_window_event: Option<FunctionRef<LazyWindowEventArgs, Option<Promise<()>>>>,
     pub on_device_event: Option<FunctionRef<FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Option<Promise<()>>>>,
+    pub on_events: Option<FunctionRef<EventsArgs, Option<Promise<()>>>>,
+    pub on_drag_drop: Option<FunctionRef<DragDropArgs, Option<Promise<()>>>>,
     pub on_about_to_wait: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>>,
     pub on_suspended: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>>,
     pub on_exiting: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Option<Promise<()>>>>,
@@ -81,8 +115,11 @@ pub struct ApplicationOptionsRefSync<'scope> {
     pub on_new_events: Option<FunctionRef<FnArgs<(ActiveEventLoop, StartCause)>, Unknown<'scope>>>,
     pub on_resumed: FunctionRef<FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>,
     pub on_user_event: Option<FunctionRef<FnArgs<(ActiveEventLoop, UserPayload)>, Unknown<'scope>>>,
-    pub on_window_event: FunctionRef<FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Unknown<'scope>>,
+    pub on_window_event: Option<FunctionRef<WindowEventArgs, Unknown<'scope>>>,
+    pub on_lazy_window_event: Option<FunctionRef<LazyWindowEventArgs, Unknown<'scope>>>,
     pub on_device_event: Option<FunctionRef<FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Unknown<'scope>>>,
+    pub on_events: Option<FunctionRef<EventsArgs, Unknown<'scope>>>,
+    pub on_drag_drop: Option<FunctionRef<DragDropArgs, Unknown<'scope>>>,
     pub on_about_to_wait: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>>,
     pub on_suspended: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>>,
     pub on_exiting: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Unknown<'scope>>>,
@@ -93,8 +130,11 @@ pub(crate) struct OptionsGhostHolder<Return: FromNapiValue> {
     pub(crate) on_new_events: Option<FunctionRef<FnArgs<(ActiveEventLoop, StartCause)>, Return>>,
     pub(crate) on_resumed: FunctionRef<FnArgs<(ActiveEventLoop,)>, Return>,
     pub(crate) on_user_event: Option<FunctionRef<FnArgs<(ActiveEventLoop, UserPayload)>, Return>>,
-    pub(crate) on_window_event: FunctionRef<FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Return>,
+    pub(crate) on_window_event: Option<FunctionRef<WindowEventArgs, Return>>,
+    pub(crate) on_lazy_window_event: Option<FunctionRef<LazyWindowEventArgs, Return>>,
     pub(crate) on_device_event: Option<FunctionRef<FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Return>>,
+    pub(crate) on_events: Option<FunctionRef<EventsArgs, Return>>,
+    pub(crate) on_drag_drop: Option<FunctionRef<DragDropArgs, Return>>,
     pub(crate) on_about_to_wait: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Return>>,
     pub(crate) on_suspended: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Return>>,
     pub(crate) on_exiting: Option<FunctionRef<FnArgs<(ActiveEventLoop,)>, Return>>,
@@ -110,8 +150,11 @@ pub(crate) struct OptionsFxHolder<'scope, Return: FromNapiValue> {
     pub on_new_events: Option<Function<'scope, FnArgs<(ActiveEventLoop, StartCause)>, Return>>,
     pub on_resumed: Function<'scope, FnArgs<(ActiveEventLoop,)>, Return>,
     pub on_user_event: Option<Function<'scope, FnArgs<(ActiveEventLoop, UserPayload)>, Return>>,
-    pub on_window_event: Function<'scope, FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Return>,
+    pub on_window_event: Option<Function<'scope, WindowEventArgs, Return>>,
+    pub on_lazy_window_event: Option<Function<'scope, LazyWindowEventArgs, Return>>,
     pub on_device_event: Option<Function<'scope, FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Return>>,
+    pub on_events: Option<Function<'scope, EventsArgs, Return>>,
+    pub on_drag_drop: Option<Function<'scope, DragDropArgs, Return>>,
     pub on_about_to_wait: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Return>>,
     pub on_suspended: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Return>>,
     pub on_exiting: Option<Function<'scope, FnArgs<(ActiveEventLoop,)>, Return>>,
@@ -122,8 +165,11 @@ pub(crate) struct OptionsSafeHolder<Return: 'static + FromNapiValue> {
     pub on_new_events: Option<ThreadsafeNoCallee<FnArgs<(ActiveEventLoop, StartCause)>, Return>>,
     pub on_resumed: ThreadsafeNoCallee<FnArgs<(ActiveEventLoop,)>, Return>,
     pub on_user_event: Option<ThreadsafeNoCallee<FnArgs<(ActiveEventLoop, UserPayload)>, Return>>,
-    pub on_window_event: ThreadsafeNoCallee<FnArgs<(ActiveEventLoop, WindowId, WindowEvent)>, Return>,
+    pub on_window_event: Option<ThreadsafeNoCallee<WindowEventArgs, Return>>,
+    pub on_lazy_window_event: Option<ThreadsafeNoCallee<LazyWindowEventArgs, Return>>,
     pub on_device_event: Option<ThreadsafeNoCallee<FnArgs<(ActiveEventLoop, DeviceId, DeviceEvent)>, Return>>,
+    pub on_events: Option<ThreadsafeNoCallee<EventsArgs, Return>>,
+    pub on_drag_drop: Option<ThreadsafeNoCallee<DragDropArgs, Return>>,
     pub on_about_to_wait: Option<ThreadsafeNoCallee<FnArgs<(ActiveEventLoop,)>, Return>>,
     pub on_suspended: Option<ThreadsafeNoCallee<FnArgs<(ActiveEventLoop,)>, Return>>,
     pub on_exiting: Option<ThreadsafeNoCallee<FnArgs<(ActiveEventLoop,)>, Return>>,
@@ -136,8 +182,11 @@ macro_rules! borrow_back {
             on_new_events: borrow_back!($from ( on_new_events ? @ $env )),
             on_resumed: borrow_back!($from ( on_resumed @ $env )),
             on_user_event: borrow_back!($from ( on_user_event ? @ $env )),
-            on_window_event: borrow_back!($from ( on_window_event @ $env )),
+            on_window_event: borrow_back!($from ( on_window_event ? @ $env )),
+            on_lazy_window_event: borrow_back!($from ( on_lazy_window_event ? @ $env )),
             on_device_event: borrow_back!($from ( on_device_event ? @ $env )),
+            on_events: borrow_back!($from ( on_events ? @ $env )),
+            on_drag_drop: borrow_back!($from ( on_drag_drop ? @ $env )),
             on_about_to_wait: borrow_back!($from ( on_about_to_wait ? @ $env )),
             on_suspended: borrow_back!($from ( on_suspended ? @ $env )),
             on_exiting: borrow_back!($from ( on_exiting ? @ $env )),
@@ -158,6 +207,16 @@ impl<Return: FromNapiValue> OptionsRefHolder<Return> {
         let Self { env, options } = self;
         borrow_back!(OptionsFxHolder { options @ env })
     }
+
+    #[inline]
+    pub(crate) fn batching(&self) -> bool {
+        self.options.on_events.is_some()
+    }
+
+    #[inline]
+    pub(crate) fn lazy_window_events(&self) -> bool {
+        self.options.on_lazy_window_event.is_some()
+    }
 }
 
 impl<Return: FromNapiValue> OptionsGhostHolder<Return> {
@@ -179,8 +238,11 @@ macro_rules! create_ref {
             on_new_events: create_ref!($from ( on_new_events ? @ $env )),
             on_resumed: create_ref!($from ( on_resumed @ $env )),
             on_user_event: create_ref!($from ( on_user_event ? @ $env )),
-            on_window_event: create_ref!($from ( on_window_event @ $env )),
+            on_window_event: create_ref!($from ( on_window_event ? @ $env )),
+            on_lazy_window_event: create_ref!($from ( on_lazy_window_event ? @ $env )),
             on_device_event: create_ref!($from ( on_device_event ? @ $env )),
+            on_events: create_ref!($from ( on_events ? @ $env )),
+            on_drag_drop: create_ref!($from ( on_drag_drop ? @ $env )),
             on_about_to_wait: create_ref!($from ( on_about_to_wait ? @ $env )),
             on_suspended: create_ref!($from ( on_suspended ? @ $env )),
             on_exiting: create_ref!($from ( on_exiting ? @ $env )),
@@ -200,6 +262,16 @@ impl<'scope, Return: FromNapiValue> OptionsFxHolder<'scope, Return> {
     pub(crate) fn create_ref(&self) -> OptionsGhostHolder<Return> {
         create_ref!(OptionsGhostHolder { self @ env })
     }
+
+    #[inline]
+    pub(crate) fn batching(&self) -> bool {
+        self.on_events.is_some()
+    }
+
+    #[inline]
+    pub(crate) fn lazy_window_events(&self) -> bool {
+        self.on_lazy_window_event.is_some()
+    }
 }
 
 macro_rules! direct_refs {
@@ -209,7 +281,10 @@ macro_rules! direct_refs {
             on_resumed: $from.on_resumed,
             on_user_event: $from.on_user_event,
             on_window_event: $from.on_window_event,
+            on_lazy_window_event: $from.on_lazy_window_event,
             on_device_event: $from.on_device_event,
+            on_events: $from.on_events,
+            on_drag_drop: $from.on_drag_drop,
             on_about_to_wait: $from.on_about_to_wait,
             on_suspended: $from.on_suspended,
             on_exiting: $from.on_exiting,
@@ -248,8 +323,11 @@ macro_rules! build_threadsafe {
             on_new_events: build_threadsafe!($from ( on_new_events ? )),
             on_resumed: build_threadsafe!($from ( on_resumed )),
             on_user_event: build_threadsafe!($from ( on_user_event ? )),
-            on_window_event: build_threadsafe!($from ( on_window_event )),
+            on_window_event: build_threadsafe!($from ( on_window_event ? )),
+            on_lazy_window_event: build_threadsafe!($from ( on_lazy_window_event ? )),
             on_device_event: build_threadsafe!($from ( on_device_event ? )),
+            on_events: build_threadsafe!($from ( on_events ? )),
+            on_drag_drop: build_threadsafe!($from ( on_drag_drop ? )),
             on_about_to_wait: build_threadsafe!($from ( on_about_to_wait ? )),
             on_suspended: build_threadsafe!($from ( on_suspended ? )),
             on_exiting: build_threadsafe!($from ( on_exiting ? )),
@@ -264,6 +342,18 @@ macro_rules! build_threadsafe {
     };
 }
 
+impl<Return: 'static + FromNapiValue> OptionsSafeHolder<Return> {
+    #[inline]
+    pub(crate) fn batching(&self) -> bool {
+        self.on_events.is_some()
+    }
+
+    #[inline]
+    pub(crate) fn lazy_window_events(&self) -> bool {
+        self.on_lazy_window_event.is_some()
+    }
+}
+
 impl<'scope> From<ApplicationOptionsFxAsync<'scope>> for OptionsSafeHolder<Option<Promise<()>>> {
     fn from(options: ApplicationOptionsFxAsync<'scope>) -> Self {
         build_threadsafe!(OptionsSafeHolder { ..options })
@@ -290,45 +380,97 @@ impl<'env> Runner<'env> {
     }
 }
 
+/// Window events have no default handler, without one of these they would be dropped silently.
+/// Evaluates to which of them are set, `setHandlers` keeps at least one
+macro_rules! require_window_handler {
+    ($options: ident) => {{
+        let window_handlers = [$options.on_window_event.is_some(), $options.on_lazy_window_event.is_some(), $options.on_events.is_some()];
+        if !window_handlers.contains(&true) {
+            return Err(napi_reason!("one of `onWindowEvent`, `onLazyWindowEvent` or `onEvents` is required"));
+        }
+        window_handlers
+    }};
+}
+
 #[napi]
 pub struct Application<'env> {
     pub(crate) env: Env,
     pub(crate) runner: Runner<'env>,
+    pub(crate) layers: Layers,
+    pub(crate) pending: PendingHandlers,
 }
 
 #[napi]
 impl<'env> Application<'env> {
     #[napi(factory)]
-    pub fn with_async_ref(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsRefAsync) -> Self {
+    pub fn with_async_ref(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsRefAsync) -> Result<Self> {
+        let window_handlers = require_window_handler!(options);
         let runner = Runner::AsyncRef(OptionsRefHolder { env, options: From::from(options) });
-        Self { env, runner }
+        Ok(Self { env, runner, layers: Layers::new(env), pending: PendingHandlers::new(env, window_handlers) })
     }
     #[napi(factory)]
-    pub fn with_sync_ref(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsRefSync<'env>) -> Self {
+    pub fn with_sync_ref(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsRefSync<'env>) -> Result<Self> {
+        let window_handlers = require_window_handler!(options);
         let runner = Runner::SyncRef(OptionsRefHolder { env, options: From::from(options) });
-        Self { env, runner }
+        Ok(Self { env, runner, layers: Layers::new(env), pending: PendingHandlers::new(env, window_handlers) })
     }
 }
 
 #[napi]
 impl<'env> Application<'env> {
     #[napi(factory)]
-    pub fn with_async_fx(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsFxAsync<'env>) -> Self {
+    pub fn with_async_fx(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsFxAsync<'env>) -> Result<Self> {
+        let window_handlers = require_window_handler!(options);
         let runner = Runner::AsyncFx(From::from(options));
-        Self { env, runner }
+        Ok(Self { env, runner, layers: Layers::new(env), pending: PendingHandlers::new(env, window_handlers) })
     }
     #[napi(factory)]
-    pub fn with_sync_fx(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsFxSync<'env>) -> Self {
+    pub fn with_sync_fx(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsFxSync<'env>) -> Result<Self> {
+        let window_handlers = require_window_handler!(options);
         let runner = Runner::SyncFx(From::from(options));
-        Self { env, runner }
+        Ok(Self { env, runner, layers: Layers::new(env), pending: PendingHandlers::new(env, window_handlers) })
     }
 }
 
 #[napi]
 impl<'env> Application<'env> {
+    /// Handlers are queued through threadsafe functions and run after winit's call returned, the
+    /// `ActiveEventLoop` and events they get stay usable until `runApp` returns
     #[napi(factory)]
-    pub fn with_async_fx_2_safe(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsFxAsync<'env>) -> Self {
+    pub fn with_async_fx_2_safe(env: Env, #[napi(ts_arg_type = "ApplicationOptions")] options: ApplicationOptionsFxAsync<'env>) -> Result<Self> {
+        let window_handlers = require_window_handler!(options);
         let runner = Runner::SafeCall(From::from(options));
-        Self { env, runner }
+        Ok(Self { env, runner, layers: Layers::new(env), pending: PendingHandlers::new(env, window_handlers) })
+    }
+}
+
+#[napi]
+impl<'env> Application<'env> {
+    /// Adds a layer in front of the handlers, lower `order` runs first, layers of the same order run in
+    /// the order they were added
+    #[napi(js_name = "use")]
+    pub fn use_layer(&mut self, #[napi(ts_arg_type = "ApplicationLayer")] layer: Unknown<'env>, order: Option<i32>) -> Result<LayerHandle> {
+        if let Runner::SafeCall(_) = self.runner {
+            return Err(napi_reason!("layers are not supported by applications made with `withAsyncFx2Safe`"));
+        }
+        self.layers.add(layer, order.unwrap_or(0))
     }
-}
\ No newline at end of file
+}
+
+#[napi]
+impl<'env> Application<'env> {
+    /// Replaces the given handlers right before the next event, the others are kept.
+    ///
+    /// `null` removes a handler, `onResumed` can only be replaced and one of `onWindowEvent`, `onLazyWindowEvent`
+    /// or `onEvents` has to be left. Only for `withSyncRef` and `withAsyncRef`,
+    /// the other runners can't keep functions past the call they were passed in
+    #[napi]
+    pub fn set_handlers(&mut self, #[napi(ts_arg_type = "Partial<ApplicationOptions>")] handlers: Unknown<'env>) -> Result<()> {
+        match self.runner {
+            Runner::SafeCall(_) => return Err(napi_reason!("handlers can't be replaced on applications made with `withAsyncFx2Safe`")),
+            Runner::AsyncFx(_) | Runner::SyncFx(_) => return Err(napi_reason!("handlers can only be replaced on applications made with `withSyncRef` or `withAsyncRef`")),
+            Runner::AsyncRef(_) | Runner::SyncRef(_) => {}
+        }
+        self.pending.push(handlers)
+    }
+}
@@ -0,0 +1,222 @@
+use std::{cell::RefCell, rc::Rc};
+
+use napi::{bindgen_prelude::*, ValueType};
+use winit::application::ApplicationHandler;
+
+use crate::{
+    application::public::OptionsRefHolder,
+    event::UserPayload,
+    napi_reason,
+    print_err,
+};
+
+/// Names `setHandlers` reads, `onResumed` can be replaced but not cleared
+const HANDLERS: [&str; 12] = [
+    "onNewEvents",
+    "onResumed",
+    "onUserEvent",
+    "onWindowEvent",
+    "onLazyWindowEvent",
+    "onDeviceEvent",
+    "onEvents",
+    "onDragDrop",
+    "onAboutToWait",
+    "onSuspended",
+    "onExiting",
+    "onMemoryWarning",
+];
+
+/// Names of which at least one has to be left after a swap
+const WINDOW_HANDLERS: [&str; 3] = ["onWindowEvent", "onLazyWindowEvent", "onEvents"];
+
+struct Pending {
+    env: Env,
+    objects: Vec<ObjectRef>,
+    /// Which of `WINDOW_HANDLERS` are set once the waiting handlers are applied
+    window_handlers: [bool; 3],
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        for object in self.objects.drain(..) {
+            let _ = object.unref(&self.env);
+        }
+    }
+}
+
+/// Handlers given to `Application.setHandlers`, waiting for the next event
+#[derive(Clone)]
+pub(crate) struct PendingHandlers(Rc<RefCell<Pending>>);
+
+impl PendingHandlers {
+    pub(crate) fn new(env: Env, window_handlers: [bool; 3]) -> Self {
+        Self(Rc::new(RefCell::new(Pending { env, objects: Vec::new(), window_handlers })))
+    }
+
+    /// Checks every handler up front, a swap can't fail halfway through
+    pub(crate) fn push(&self, handlers: Unknown) -> Result<()> {
+        if handlers.get_type()? != ValueType::Object {
+            return Err(napi_reason!("handlers must be an object, like `{{ onWindowEvent() {{}} }}`"));
+        }
+        let object = unsafe { handlers.cast::<Object>()? };
+
+        for name in HANDLERS {
+            match type_of(&object, name)? {
+                ValueType::Undefined | ValueType::Function => {}
+                ValueType::Null if name != "onResumed" => {}
+                _ => return Err(napi_reason!("`{name}` must be a function{}", if name == "onResumed" { "" } else { ", null or undefined" })),
+            }
+        }
+
+        let mut pending = self.0.borrow_mut();
+        let mut window_handlers = pending.window_handlers;
+        for (set, name) in window_handlers.iter_mut().zip(WINDOW_HANDLERS) {
+            match type_of(&object, name)? {
+                ValueType::Function => *set = true,
+                ValueType::Null => *set = false,
+                _ => {}
+            }
+        }
+        if !window_handlers.contains(&true) {
+            return Err(napi_reason!("the swap would leave no window handler, one of `onWindowEvent`, `onLazyWindowEvent` or `onEvents` is required"));
+        }
+
+        pending.objects.push(object.create_ref()?);
+        pending.window_handlers = window_handlers;
+        Ok(())
+    }
+
+    /// Moves the waiting handlers into `handler`, in the order they were set
+    fn apply<H: SwapHandlers>(&self, handler: &mut H) -> Result<()> {
+        let mut pending = self.0.borrow_mut();
+        if pending.objects.is_empty() {
+            return Ok(());
+        }
+        let env = pending.env;
+        let mut result = Ok(());
+        for reference in std::mem::take(&mut pending.objects) {
+            // after an error the rest is only released
+            if result.is_ok() {
+                result = reference.get_value(&env).and_then(|object| handler.swap_handlers(&object));
+            }
+            let _ = reference.unref(&env);
+        }
+        result
+    }
+}
+
+fn type_of(object: &Object, name: &str) -> Result<ValueType> {
+    object.get_named_property_unchecked::<Unknown>(name)?.get_type()
+}
+
+enum Swap<T> {
+    Keep,
+    Clear,
+    Set(T),
+}
+
+fn read<T: FromNapiValue>(object: &Object, name: &str) -> Result<Swap<T>> {
+    match type_of(object, name)? {
+        ValueType::Undefined => Ok(Swap::Keep),
+        ValueType::Null => Ok(Swap::Clear),
+        _ => Ok(Swap::Set(object.get_named_property_unchecked(name)?)),
+    }
+}
+
+macro_rules! swap_handlers {
+    ($target: expr, $object: ident) => {
+        swap_handlers!($target, $object, on_new_events? = "onNewEvents");
+        swap_handlers!($target, $object, on_resumed = "onResumed");
+        swap_handlers!($target, $object, on_user_event? = "onUserEvent");
+        swap_handlers!($target, $object, on_window_event? = "onWindowEvent");
+        swap_handlers!($target, $object, on_lazy_window_event? = "onLazyWindowEvent");
+        swap_handlers!($target, $object, on_device_event? = "onDeviceEvent");
+        swap_handlers!($target, $object, on_events? = "onEvents");
+        swap_handlers!($target, $object, on_drag_drop? = "onDragDrop");
+        swap_handlers!($target, $object, on_about_to_wait? = "onAboutToWait");
+        swap_handlers!($target, $object, on_suspended? = "onSuspended");
+        swap_handlers!($target, $object, on_exiting? = "onExiting");
+        swap_handlers!($target, $object, on_memory_warning? = "onMemoryWarning");
+    };
+    ($target: expr, $object: ident, $name: ident? = $js_name: literal) => {
+        match read($object, $js_name)? {
+            Swap::Keep => {}
+            Swap::Clear => $target.$name = None,
+            Swap::Set(handler) => $target.$name = Some(handler),
+        }
+    };
+    ($target: expr, $object: ident, $name: ident = $js_name: literal) => {
+        if let Swap::Set(handler) = read($object, $js_name)? {
+            $target.$name = handler;
+        }
+    };
+}
+
+pub(crate) trait SwapHandlers {
+    fn swap_handlers(&mut self, object: &Object) -> Result<()>;
+}
+
+impl<Return: FromNapiValue> SwapHandlers for OptionsRefHolder<Return> {
+    fn swap_handlers(&mut self, object: &Object) -> Result<()> {
+        swap_handlers!(self.options, object);
+        Ok(())
+    }
+}
+
+/// Applies the handlers given to `setHandlers` before each event reaches the application
+pub(crate) struct Swappable<'a, H> {
+    handler: &'a mut H,
+    pending: PendingHandlers,
+}
+
+impl<'a, H: SwapHandlers> Swappable<'a, H> {
+    pub(crate) fn new(handler: &'a mut H, pending: &PendingHandlers) -> Self {
+        Self { handler, pending: pending.clone() }
+    }
+
+    #[inline]
+    fn swap(&mut self) -> &mut H {
+        if let Err(err) = self.pending.apply(&mut *self.handler) {
+            print_err!(err);
+        }
+        &mut *self.handler
+    }
+}
+
+impl<H: SwapHandlers + ApplicationHandler<UserPayload>> ApplicationHandler<UserPayload> for Swappable<'_, H> {
+    fn new_events(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, cause: winit::event::StartCause) {
+        self.swap().new_events(event_loop, cause);
+    }
+
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.swap().resumed(event_loop);
+    }
+
+    fn user_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, event: UserPayload) {
+        self.swap().user_event(event_loop, event);
+    }
+
+    fn window_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId, event: winit::event::WindowEvent) {
+        self.swap().window_event(event_loop, window_id, event);
+    }
+
+    fn device_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, device_id: winit::event::DeviceId, event: winit::event::DeviceEvent) {
+        self.swap().device_event(event_loop, device_id, event);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.swap().about_to_wait(event_loop);
+    }
+
+    fn suspended(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.swap().suspended(event_loop);
+    }
+
+    fn exiting(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.swap().exiting(event_loop);
+    }
+
+    fn memory_warning(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.swap().memory_warning(event_loop);
+    }
+}
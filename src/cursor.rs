@@ -4,6 +4,7 @@ use crate::napi_reason;
 
 /** [`winit::window::CursorIcon`] */
 #[proxy_enum(origin_type = winit::window::CursorIcon, string_enum, non_exhaustive)]
+#[derive(Clone)]
 pub enum CursorIcon {
     Default, ContextMenu, Help, Pointer, Progress, Wait, Cell, Crosshair, Text, VerticalText,
     Alias, Copy, Move, NoDrop, NotAllowed, Grab, Grabbing, EResize, NResize, NeResize, NwResize,
@@ -7,10 +7,14 @@ use crate::{
     event_loop::AsyncRequestSerial,
     extra::time::Instant,
     keyboard::{Key, KeyLocation, ModifiersState, PhysicalKey},
-    utils::helpers::{option_into, path_buf_to_string},
+    utils::{helpers::{option_into, path_buf_to_string, path_to_bytes}, scope::Stamp},
     window::{ActivationToken, Theme, WindowId},
+    napi_reason,
 };
 
+mod lazy;
+pub use lazy::LazyWindowEvent;
+
 #[napi]
 #[derive(Clone)]
 pub struct UserPayload {}
@@ -122,6 +126,7 @@ pub enum WindowEvent {
     Touch(#[proxy_enum(field_name = touch)] Touch),
     ScaleFactorChanged {
         scale_factor: f64,
+        #[proxy_enum(from_origin = { InnerSizeWriter::new(inner_size_writer, scale_factor) })]
         inner_size_writer: InnerSizeWriter,
     },
     ThemeChanged(#[proxy_enum(field_name = theme)] Theme),
@@ -129,6 +134,45 @@ pub enum WindowEvent {
     RedrawRequested,
 }
 
+/// Path of a dragged file
+#[napi(object, object_from_js = false)]
+pub struct DroppedPath {
+    /// Invalid UTF-8 is replaced with U+FFFD, see `lossy`
+    pub path: String,
+    /// Exact bytes of the path, raw on unix and WTF-8 on windows
+    pub raw: Buffer,
+    pub lossy: bool,
+}
+
+impl From<std::path::PathBuf> for DroppedPath {
+    fn from(path: std::path::PathBuf) -> Self {
+        Self {
+            raw: path_to_bytes(&path).into(),
+            lossy: path.to_str().is_none(),
+            path: path_buf_to_string(path),
+        }
+    }
+}
+
+/// The `HoveredFile`, `DroppedFile` and `HoveredFileCancelled` events of one drag, grouped.
+///
+/// Positions come from the latest `CursorMoved` of the window, `None` when the cursor was never seen.
+#[napi(object_from_js = false)]
+pub enum DragDropEvent {
+    DragEnter {
+        files: Vec<DroppedPath>,
+        position: Option<Position>,
+    },
+    DragOver {
+        position: Position,
+    },
+    Drop {
+        files: Vec<DroppedPath>,
+        position: Option<Position>,
+    },
+    DragLeave,
+}
+
 /**[winit::event::DeviceId]*/
 #[proxy_wrap(origin_type = winit::event::DeviceId)]
 pub struct DeviceId;
@@ -181,7 +225,7 @@ pub enum Ime {
     Disabled,
 }
 
-#[proxy_enum(origin_type = winit::event::MouseButton, skip_backward)]
+#[proxy_enum(origin_type = winit::event::MouseButton, skip_backward, serde)]
 pub enum MouseButton {
     Left,
     Right,
@@ -198,10 +242,38 @@ pub enum MouseScrollDelta {
 }
 
 /**[winit::event::InnerSizeWriter]*/
-#[proxy_wrap(origin_type = winit::event::InnerSizeWriter)]
-pub struct InnerSizeWriter;
+#[napi]
+pub struct InnerSizeWriter {
+    pub(crate) inner: winit::event::InnerSizeWriter,
+    pub(crate) scale_factor: f64,
+    stamp: Stamp,
+}
+
+impl InnerSizeWriter {
+    pub(crate) fn new(inner: winit::event::InnerSizeWriter, scale_factor: f64) -> Self {
+        Self { inner, scale_factor, stamp: Stamp::now() }
+    }
+}
+
+#[napi]
+impl InnerSizeWriter {
+    /// The new scale factor, used to convert logical sizes
+    #[napi(getter)]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Size the window gets once the `ScaleFactorChanged` handler returns
+    #[napi]
+    pub fn request_inner_size(&mut self, size: Size) -> Result<()> {
+        self.stamp.check("InnerSizeWriter")?;
+        let size = Into::<winit::dpi::Size>::into(size).to_physical::<u32>(self.scale_factor);
+        self.inner.request_inner_size(size)
+            .map_err(|_| napi_reason!("InnerSizeWriter has expired, requestInnerSize only works while the ScaleFactorChanged handler runs"))
+    }
+}
 
-#[proxy_enum(origin_type = winit::event::TouchPhase, string_enum, skip_backward)]
+#[proxy_enum(origin_type = winit::event::TouchPhase, string_enum, skip_backward, serde)]
 pub enum TouchPhase {
     Started,
     Moved,
@@ -283,7 +355,7 @@ pub enum DeviceEvent {
     Key(#[proxy_enum(field_name = raw)] RawKeyEvent),
 }
 
-#[proxy_enum(origin_type = winit::event::ElementState, string_enum, skip_backward)]
+#[proxy_enum(origin_type = winit::event::ElementState, string_enum, skip_backward, serde)]
 #[derive(Clone)]
 pub enum ElementState {
     Pressed,
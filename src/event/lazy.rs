@@ -0,0 +1,143 @@
+use napi::{bindgen_prelude::*, UnknownRef};
+use winit::event::WindowEvent as OriginWindowEvent;
+
+use crate::{
+    dpi::Position,
+    event::{ElementState, KeyEvent, WindowEvent},
+    utils::scope::Stamp,
+};
+
+/// `WindowEvent` kept native until read, `type` and the hot fields are answered without converting the rest
+#[napi]
+pub struct LazyWindowEvent {
+    inner: OriginWindowEvent,
+    /// The js object `event` returned, released with the lazy event
+    converted: Option<(Env, UnknownRef)>,
+    /// `event` may hold an `InnerSizeWriter`, which is only valid during the handler call
+    stamp: Stamp,
+}
+
+impl From<OriginWindowEvent> for LazyWindowEvent {
+    fn from(inner: OriginWindowEvent) -> Self {
+        Self { inner, converted: None, stamp: Stamp::now() }
+    }
+}
+
+impl Drop for LazyWindowEvent {
+    fn drop(&mut self) {
+        if let Some((env, object)) = self.converted.take() {
+            let _ = object.unref(&env);
+        }
+    }
+}
+
+#[napi]
+impl LazyWindowEvent {
+    #[napi(getter, js_name = "type", ts_return_type = "WindowEvent['type']")]
+    pub fn kind(&self) -> &'static str {
+        match &self.inner {
+            OriginWindowEvent::ActivationTokenDone { .. } => "ActivationTokenDone",
+            OriginWindowEvent::Resized(_) => "Resized",
+            OriginWindowEvent::Moved(_) => "Moved",
+            OriginWindowEvent::CloseRequested => "CloseRequested",
+            OriginWindowEvent::Destroyed => "Destroyed",
+            OriginWindowEvent::DroppedFile(_) => "DroppedFile",
+            OriginWindowEvent::HoveredFile(_) => "HoveredFile",
+            OriginWindowEvent::HoveredFileCancelled => "HoveredFileCancelled",
+            OriginWindowEvent::Focused(_) => "Focused",
+            OriginWindowEvent::KeyboardInput { .. } => "KeyboardInput",
+            OriginWindowEvent::ModifiersChanged(_) => "ModifiersChanged",
+            OriginWindowEvent::Ime(_) => "Ime",
+            OriginWindowEvent::CursorMoved { .. } => "CursorMoved",
+            OriginWindowEvent::CursorEntered { .. } => "CursorEntered",
+            OriginWindowEvent::CursorLeft { .. } => "CursorLeft",
+            OriginWindowEvent::MouseWheel { .. } => "MouseWheel",
+            OriginWindowEvent::MouseInput { .. } => "MouseInput",
+            OriginWindowEvent::PinchGesture { .. } => "PinchGesture",
+            OriginWindowEvent::PanGesture { .. } => "PanGesture",
+            OriginWindowEvent::DoubleTapGesture { .. } => "DoubleTapGesture",
+            OriginWindowEvent::RotationGesture { .. } => "RotationGesture",
+            OriginWindowEvent::TouchpadPressure { .. } => "TouchpadPressure",
+            OriginWindowEvent::AxisMotion { .. } => "AxisMotion",
+            OriginWindowEvent::Touch(_) => "Touch",
+            OriginWindowEvent::ScaleFactorChanged { .. } => "ScaleFactorChanged",
+            OriginWindowEvent::ThemeChanged(_) => "ThemeChanged",
+            OriginWindowEvent::Occluded(_) => "Occluded",
+            OriginWindowEvent::RedrawRequested => "RedrawRequested",
+        }
+    }
+
+    /// The whole event, converted on the first read, later reads return the same object.
+    /// Only readable during the handler call, like `ActiveEventLoop`
+    #[napi(getter, ts_return_type = "WindowEvent")]
+    pub fn event<'env>(&mut self, env: &'env Env) -> Result<Unknown<'env>> {
+        self.stamp.check("LazyWindowEvent.event")?;
+        if let Some((_, object)) = &self.converted {
+            return object.get_value(env);
+        }
+
+        let event = WindowEvent::from(self.inner.clone()).into_unknown(env)?;
+        self.converted = Some((*env, event.create_ref()?));
+        Ok(event)
+    }
+
+    /// `KeyboardInput` only
+    #[napi(getter)]
+    pub fn key(&self) -> Option<KeyEvent> {
+        match &self.inner {
+            OriginWindowEvent::KeyboardInput { event, .. } => Some(event.clone().into()),
+            _ => None,
+        }
+    }
+
+    /// `CursorMoved` only
+    #[napi(getter)]
+    pub fn position(&self) -> Option<Position> {
+        position_of(&self.inner)
+    }
+
+    /// `KeyboardInput` and `MouseInput` only
+    #[napi(getter)]
+    pub fn state(&self) -> Option<ElementState> {
+        state_of(&self.inner)
+    }
+}
+
+fn position_of(event: &OriginWindowEvent) -> Option<Position> {
+    match event {
+        OriginWindowEvent::CursorMoved { position, .. } => Some((*position).into()),
+        _ => None,
+    }
+}
+
+fn state_of(event: &OriginWindowEvent) -> Option<ElementState> {
+    match event {
+        OriginWindowEvent::KeyboardInput { event, .. } => Some(event.state.into()),
+        OriginWindowEvent::MouseInput { state, .. } => Some((*state).into()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{
+        dpi::PhysicalPosition,
+        event::{DeviceId, ElementState as OriginElementState, MouseButton, WindowEvent as OriginWindowEvent},
+    };
+
+    use super::{position_of, state_of};
+    use crate::{dpi::Position, event::ElementState};
+
+    #[test]
+    fn test_hot_fields() {
+        let device_id = DeviceId::dummy();
+
+        let moved = OriginWindowEvent::CursorMoved { device_id, position: PhysicalPosition::new(3., 4.) };
+        assert!(matches!(position_of(&moved), Some(Position::Physical { x: 3., y: 4. })));
+        assert!(state_of(&moved).is_none());
+
+        let pressed = OriginWindowEvent::MouseInput { device_id, state: OriginElementState::Pressed, button: MouseButton::Left };
+        assert!(matches!(state_of(&pressed), Some(ElementState::Pressed)));
+        assert!(position_of(&pressed).is_none());
+    }
+}
@@ -11,17 +11,25 @@ use winit::platform::{
 };
 
 use proc::{proxy_enum, proxy_wrap};
+use rwh_06::HasDisplayHandle;
 
 use crate::{
-    application::public::{Application, Runner},
+    application::{batch, batch::EventBatchOptions, middleware, public::{Application, Runner}, swap::Swappable},
     cursor::{CustomCursor, CustomCursorSource},
     event::UserPayload,
-    extra::time::Instant,
+    extra::{raw_window::rwh_06_convert, time::Instant},
     monitor::MonitorHandle,
     napi_reason,
-    window::{Theme, Window, WindowAttributes},
+    utils::scope::{self, Stamp},
+    window::{platform, registry, registry::WindowRegistryOptions, ActivationToken, PendingAttributes, Theme, Window, WindowAttributes, WindowId},
 };
 
+mod background;
+mod libuv;
+
+pub use background::{BackgroundEvent, BackgroundEventLoop};
+pub use libuv::LibuvDriver;
+
 #[proxy_wrap(origin_type = winit::event_loop::EventLoop::<UserPayload>, field_name = inner)]
 pub struct EventLoop;
 
@@ -32,6 +40,102 @@ impl EventLoop {
         let event_loop = winit::event_loop::EventLoop::<UserPayload>::with_user_event().build().expect("Failed to build EventLoop");
         Self { inner: event_loop }
     }
+    /// Like the constructor, but throws instead of aborting when no display is available
+    #[napi(factory)]
+    pub fn create(options: Option<EventLoopOptions>) -> Result<Self> {
+        let EventLoopOptions { backend, any_thread, app_id } = options.unwrap_or_default();
+        let mut builder = winit::event_loop::EventLoop::<UserPayload>::with_user_event();
+        configure_builder(&mut builder, backend, any_thread)?;
+        let event_loop = builder.build().map_err(|e| napi_reason!("{e}"))?;
+        platform::set_default_app_id(app_id);
+        Ok(Self { inner: event_loop })
+    }
+    /// Display backend winit connected to
+    #[napi]
+    pub fn backend(&self) -> Result<DisplayBackend> {
+        let display = self.inner.display_handle().map_err(|e| napi_reason!("{e}"))?;
+        Ok(DisplayBackend::from(&display.as_raw()))
+    }
+}
+
+#[napi(string_enum)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayBackend {
+    X11,
+    Wayland,
+    Windows,
+    AppKit,
+    Other,
+}
+
+impl From<&rwh_06::RawDisplayHandle> for DisplayBackend {
+    fn from(display: &rwh_06::RawDisplayHandle) -> Self {
+        match display {
+            rwh_06::RawDisplayHandle::Xlib(_) | rwh_06::RawDisplayHandle::Xcb(_) => DisplayBackend::X11,
+            rwh_06::RawDisplayHandle::Wayland(_) => DisplayBackend::Wayland,
+            rwh_06::RawDisplayHandle::Windows(_) => DisplayBackend::Windows,
+            rwh_06::RawDisplayHandle::AppKit(_) => DisplayBackend::AppKit,
+            _ => DisplayBackend::Other,
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct EventLoopOptions {
+    /// `X11` or `Wayland`, by default winit picks from `WAYLAND_DISPLAY` and `DISPLAY`
+    pub backend: Option<DisplayBackend>,
+    /// Allow the event loop outside of the main thread, X11, Wayland and Windows only
+    pub any_thread: Option<bool>,
+    /// Wayland app id and X11 `WM_CLASS` of the windows that don't set their own
+    pub app_id: Option<String>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+fn configure_builder<T: 'static>(builder: &mut winit::event_loop::EventLoopBuilder<T>, backend: Option<DisplayBackend>, any_thread: Option<bool>) -> Result<()> {
+    use winit::platform::{wayland::EventLoopBuilderExtWayland, x11::EventLoopBuilderExtX11};
+
+    match backend {
+        None => {}
+        Some(DisplayBackend::X11) => { builder.with_x11(); }
+        Some(DisplayBackend::Wayland) => { builder.with_wayland(); }
+        Some(backend) => return Err(napi_reason!("backend must be `X11` or `Wayland` on this platform, got `{backend:?}`")),
+    }
+    if let Some(any_thread) = any_thread {
+        EventLoopBuilderExtX11::with_any_thread(builder, any_thread);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn configure_builder<T: 'static>(builder: &mut winit::event_loop::EventLoopBuilder<T>, backend: Option<DisplayBackend>, any_thread: Option<bool>) -> Result<()> {
+    use winit::platform::windows::EventLoopBuilderExtWindows;
+
+    if let Some(backend) = backend.filter(|backend| *backend != DisplayBackend::Windows) {
+        return Err(napi_reason!("backend `{backend:?}` is not available on Windows"));
+    }
+    if let Some(any_thread) = any_thread {
+        builder.with_any_thread(any_thread);
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "windows")))]
+fn configure_builder<T: 'static>(_builder: &mut winit::event_loop::EventLoopBuilder<T>, backend: Option<DisplayBackend>, any_thread: Option<bool>) -> Result<()> {
+    if backend.is_some_and(|backend| backend != DisplayBackend::AppKit) {
+        return Err(napi_reason!("the display backend can't be chosen on this platform"));
+    }
+    if any_thread == Some(true) {
+        return Err(napi_reason!("`anyThread` is only supported on X11, Wayland and Windows"));
+    }
+    Ok(())
+}
+
+fn refuse_while_driven() -> Result<()> {
+    if libuv::driving() {
+        return Err(napi_reason!("the event loop is driven by `runAppOnLibuv`, stop the driver first"));
+    }
+    Ok(())
 }
 
 #[proxy_enum(origin_type = winit::platform::pump_events::PumpStatus, skip_backward)]
@@ -45,26 +149,31 @@ impl EventLoop {
     // with_user_event
     #[napi]
     pub fn run_app(&mut self, env: Env, app: &mut Application) -> Result<()> {
+        refuse_while_driven()?;
         let this = unsafe { Box::from_raw(self as *const _ as *mut EventLoop) };
 
+        let _layers = middleware::Installed::new(&app.layers);
         let result = match app.runner {
             Runner::AsyncFx(ref mut handler) => this.inner.run_app(handler),
             Runner::SyncFx(ref mut handler) => this.inner.run_app(handler),
-            Runner::AsyncRef(ref mut handler) => this.inner.run_app(handler),
-            Runner::SyncRef(ref mut handler) => this.inner.run_app(handler),
+            Runner::AsyncRef(ref mut handler) => this.inner.run_app(&mut Swappable::new(handler, &app.pending)),
+            Runner::SyncRef(ref mut handler) => this.inner.run_app(&mut Swappable::new(handler, &app.pending)),
             Runner::SafeCall(ref mut handler) => this.inner.run_app(handler),
         };
+        scope::loop_consumed();
 
         result.map_err(|e| napi_reason!("{e}"))
     }
 
     #[napi]
     pub fn run_app_on_demand(&mut self, env: Env, app: &mut Application) -> Result<()> {
+        refuse_while_driven()?;
+        let _layers = middleware::Installed::new(&app.layers);
         let result = match app.runner {
             Runner::AsyncFx(ref mut handler) => self.inner.run_app_on_demand(handler),
             Runner::SyncFx(ref mut handler) => self.inner.run_app_on_demand(handler),
-            Runner::AsyncRef(ref mut handler) => self.inner.run_app_on_demand(handler),
-            Runner::SyncRef(ref mut handler) => self.inner.run_app_on_demand(handler),
+            Runner::AsyncRef(ref mut handler) => self.inner.run_app_on_demand(&mut Swappable::new(handler, &app.pending)),
+            Runner::SyncRef(ref mut handler) => self.inner.run_app_on_demand(&mut Swappable::new(handler, &app.pending)),
             Runner::SafeCall(ref mut handler) => self.inner.run_app_on_demand(handler),
         };
 
@@ -72,21 +181,35 @@ impl EventLoop {
     }
 
     #[napi]
-    pub fn pump_app_events(&mut self, env: Env, millis: f64, app: &mut Application) -> PumpStatus {
+    pub fn pump_app_events(&mut self, env: Env, millis: f64, app: &mut Application) -> Result<PumpStatus> {
+        refuse_while_driven()?;
         let timeout = Some(Duration::from_millis(millis as u64));
 
+        let _layers = middleware::Installed::new(&app.layers);
         let result = match app.runner {
             Runner::AsyncFx(ref mut handler) => self.inner.pump_app_events(timeout, handler),
             Runner::SyncFx(ref mut handler) => self.inner.pump_app_events(timeout, handler),
-            Runner::AsyncRef(ref mut handler) => self.inner.pump_app_events(timeout, handler),
-            Runner::SyncRef(ref mut handler) => self.inner.pump_app_events(timeout, handler),
+            Runner::AsyncRef(ref mut handler) => self.inner.pump_app_events(timeout, &mut Swappable::new(handler, &app.pending)),
+            Runner::SyncRef(ref mut handler) => self.inner.pump_app_events(timeout, &mut Swappable::new(handler, &app.pending)),
             Runner::SafeCall(ref mut handler) => self.inner.pump_app_events(timeout, handler),
         };
 
-        PumpStatus::from(result)
+        Ok(PumpStatus::from(result))
+    }
+    /// Pumps `app` from the libuv loop of node instead of blocking it, whenever the display connection
+    /// is readable or the `WaitUntil` deadline of the control flow passes.
+    ///
+    /// X11 and Wayland only, the driver keeps `eventLoop` and `app` alive until it stops. Applications made
+    /// with `withSyncFx` or `withAsyncFx` are refused
+    #[napi]
+    pub fn run_app_on_libuv(&mut self, env: Env, this: This, app: ClassInstance<Application>) -> Result<LibuvDriver> {
+        libuv::drive(env, self, this, app)
     }
     // create_proxy
-    // owned_display_handle
+    #[napi]
+    pub fn owned_display_handle(&self) -> OwnedDisplayHandle {
+        self.inner.owned_display_handle().into()
+    }
     // listen_device_events
     // create_window
     // create_custom_cursor
@@ -95,12 +218,19 @@ impl EventLoop {
 #[napi]
 pub struct ActiveEventLoop {
     pub(crate) inner_non_null: NonNull<winit::event_loop::ActiveEventLoop>,
+    stamp: Stamp,
 }
 
 impl ActiveEventLoop {
     pub fn new(origin: &winit::event_loop::ActiveEventLoop) -> Self {
         let non_null = NonNull::new(origin as *const _ as *mut winit::event_loop::ActiveEventLoop).unwrap();
-        Self { inner_non_null: non_null }
+        Self { inner_non_null: non_null, stamp: Stamp::now() }
+    }
+
+    /// winit only lends the loop for the handler call, later uses throw
+    pub(crate) fn inner(&self) -> Result<&winit::event_loop::ActiveEventLoop> {
+        self.stamp.check("ActiveEventLoop")?;
+        Ok(unsafe { self.inner_non_null.as_ref() })
     }
 }
 
@@ -112,57 +242,125 @@ impl From<&winit::event_loop::ActiveEventLoop> for ActiveEventLoop {
 
 macro_rules! inner_ref {
     ($self: ident) => {
-        unsafe { $self.inner_non_null.as_ref() }
+        $self.inner()?
     };
 }
 
 #[napi]
 impl ActiveEventLoop {
+    /// The window is tracked by the registry until it is closed or destroyed
+    #[napi(ts_return_type = "Window")]
+    pub fn create_window(&self, env: Env, window_attributes: &WindowAttributes) -> Result<Reference<Window>> {
+        let display = inner_ref!(self).display_handle()
+            .map_err(|e| napi_reason!("{e}"))?
+            .as_raw();
+
+        if let Some(parent) = &window_attributes.parent_window
+            && !rwh_06_convert::matches_display(parent.handle(), &display)
+        {
+            return Err(napi_reason!("the parent window does not belong to the running display backend"));
+        }
+
+        let attrs = PendingAttributes::from(window_attributes.clone()).build(&display);
+        let window = inner_ref!(self).create_window(attrs)
+            .map_err(|e| napi_reason!("{e}"))?;
+        registry::track(env, Window::from(window))
+    }
+    #[napi(ts_return_type = "Window | null")]
+    pub fn get_window(&self, window_id: &WindowId) -> Result<Option<Reference<Window>>> {
+        inner_ref!(self);
+        Ok(registry::get(window_id.0))
+    }
+    /// Tracked windows in creation order
+    #[napi(ts_return_type = "Window[]")]
+    pub fn windows(&self) -> Result<Vec<Reference<Window>>> {
+        inner_ref!(self);
+        Ok(registry::all())
+    }
+    #[napi]
+    pub fn window_count(&self) -> Result<u32> {
+        inner_ref!(self);
+        Ok(registry::count())
+    }
+    /// Destroy the window and stop tracking it, later uses of the `Window` throw. Returns false if it was not tracked
     #[napi]
-    pub fn create_window(&self, window_attributes: &WindowAttributes) -> Result<Window> {
-        inner_ref!(self).create_window(window_attributes.clone().into())
-            .map_err(|e| napi_reason!("{e}"))
-            .map(Window::from)
+    pub fn close_window(&self, window_id: &WindowId) -> Result<bool> {
+        Ok(registry::close(inner_ref!(self), window_id.0))
+    }
+    #[napi]
+    pub fn set_window_registry_options(&self, options: WindowRegistryOptions) -> Result<()> {
+        inner_ref!(self);
+        registry::set_options(options);
+        Ok(())
+    }
+    /// Typed array encoding of the events delivered to `onEvents`, from the next batch on
+    #[napi]
+    pub fn set_event_batch_options(&self, options: EventBatchOptions) -> Result<()> {
+        inner_ref!(self);
+        batch::set_options(options);
+        Ok(())
     }
     // #[napi]
     // pub fn create_custom_cursor(&self, custom_cursor: &CustomCursorSource) -> CustomCursor {
     //     self.inner.create_custom_cursor(custom_cursor.clone().into()).into()
     // }
     #[napi]
-    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
-        inner_ref!(self).available_monitors().map(|m| m.into()).collect()
+    pub fn available_monitors(&self) -> Result<Vec<MonitorHandle>> {
+        Ok(inner_ref!(self).available_monitors().map(|m| m.into()).collect())
     }
     #[napi]
-    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
-        inner_ref!(self).primary_monitor().map(|m| m.into())
+    pub fn primary_monitor(&self) -> Result<Option<MonitorHandle>> {
+        Ok(inner_ref!(self).primary_monitor().map(|m| m.into()))
     }
     #[napi]
-    pub fn listen_device_events(&self, allowed: DeviceEvents) {
-        inner_ref!(self).listen_device_events(allowed.into())
+    pub fn listen_device_events(&self, allowed: DeviceEvents) -> Result<()> {
+        inner_ref!(self).listen_device_events(allowed.into());
+        Ok(())
     }
     #[napi]
-    pub fn system_theme(&self) -> Option<Theme> {
-        inner_ref!(self).system_theme().map(|theme| theme.into())
+    pub fn system_theme(&self) -> Result<Option<Theme>> {
+        Ok(inner_ref!(self).system_theme().map(|theme| theme.into()))
     }
     #[napi]
-    pub fn set_control_flow(&self, control_flow: ControlFlow) {
-        inner_ref!(self).set_control_flow(control_flow.into())
+    pub fn set_control_flow(&self, control_flow: ControlFlow) -> Result<()> {
+        inner_ref!(self).set_control_flow(control_flow.into());
+        Ok(())
     }
     #[napi]
-    pub fn control_flow(&self) -> ControlFlow {
-        inner_ref!(self).control_flow().into()
+    pub fn control_flow(&self) -> Result<ControlFlow> {
+        Ok(inner_ref!(self).control_flow().into())
     }
     #[napi]
-    pub fn exit(&self) {
-        inner_ref!(self).exit()
+    pub fn exit(&self) -> Result<()> {
+        inner_ref!(self).exit();
+        Ok(())
     }
     #[napi]
-    pub fn exiting(&self) -> bool {
-        inner_ref!(self).exiting()
+    pub fn exiting(&self) -> Result<bool> {
+        Ok(inner_ref!(self).exiting())
     }
+    /// Token the app was launched with, from `XDG_ACTIVATION_TOKEN` on Wayland or `DESKTOP_STARTUP_ID` on X11
     #[napi]
-    pub fn owned_display_handle(&self) -> OwnedDisplayHandle {
-        inner_ref!(self).owned_display_handle().into()
+    pub fn read_token_from_env(&self) -> Result<Option<ActivationToken>> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        {
+            use winit::platform::startup_notify::EventLoopExtStartupNotify;
+            Ok(inner_ref!(self).read_token_from_env().map(ActivationToken::from))
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+        {
+            inner_ref!(self);
+            Ok(None)
+        }
+    }
+    #[napi]
+    pub fn owned_display_handle(&self) -> Result<OwnedDisplayHandle> {
+        Ok(inner_ref!(self).owned_display_handle().into())
+    }
+    #[napi]
+    pub fn backend(&self) -> Result<DisplayBackend> {
+        let display = inner_ref!(self).display_handle().map_err(|e| napi_reason!("{e}"))?;
+        Ok(DisplayBackend::from(&display.as_raw()))
     }
 }
 
@@ -184,3 +382,11 @@ pub struct OwnedDisplayHandle;
 /** [winit::event_loop::AsyncRequestSerial] */
 #[proxy_wrap(origin_type = winit::event_loop::AsyncRequestSerial)]
 pub struct AsyncRequestSerial;
+
+#[napi]
+impl AsyncRequestSerial {
+    #[napi]
+    pub fn equals(&self, other: &AsyncRequestSerial) -> bool {
+        self.0 == other.0
+    }
+}
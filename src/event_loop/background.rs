@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    rc::Rc,
+    sync::mpsc,
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use napi::{bindgen_prelude::*, threadsafe_function::ThreadsafeFunctionCallMode};
+use rwh_06::HasDisplayHandle;
+use softbuffer::{Context, Surface};
+use tokio::sync::oneshot;
+use winit::{
+    application::ApplicationHandler,
+    event_loop::{EventLoopClosed, EventLoopProxy},
+};
+
+use crate::{
+    dpi::Size,
+    event::{DeviceEvent, DeviceId, DragDropEvent, WindowEvent},
+    event_loop::{configure_builder, DisplayBackend},
+    napi_reason,
+    utils::alias::ThreadsafeNoCallee,
+    window::{self, activation, drag_drop, logical_size, platform, registry::WindowRegistryOptions, ActivationToken, PendingAttributes, WindowAttributes, WindowId},
+};
+
+/// How long `createWindow` waits for the loop thread
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events of a `BackgroundEventLoop`, delivered in order without blocking the loop thread
+#[napi(object_from_js = false)]
+pub enum BackgroundEvent {
+    Resumed,
+    Suspended,
+    Window {
+        window_id: WindowId,
+        event: WindowEvent,
+    },
+    Device {
+        device_id: DeviceId,
+        event: DeviceEvent,
+    },
+    DragDrop {
+        window_id: WindowId,
+        event: DragDropEvent,
+    },
+    Exiting,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct BackgroundEventLoopOptions {
+    pub backend: Option<DisplayBackend>,
+    pub app_id: Option<String>,
+    /// Same as `ActiveEventLoop.setWindowRegistryOptions`, applied on the loop thread
+    pub window_registry: Option<WindowRegistryOptions>,
+}
+
+type Reply<T> = mpsc::Sender<std::result::Result<T, String>>;
+
+/// Window calls proxied to the loop thread, the windows themselves never leave it
+enum WindowCommand {
+    SetTitle(String),
+    SetVisible(bool),
+    RequestInnerSize(winit::dpi::Size),
+    RequestRedraw,
+    SetKeepLogicalSize(bool),
+    RequestActivationToken(Reply<oneshot::Receiver<winit::window::ActivationToken>>),
+    Present(Frame, Reply<()>),
+    Close,
+}
+
+/// Pixels copied out of js, presented with a surface owned by the loop thread
+struct Frame {
+    pixels: Vec<u32>,
+    width: NonZeroU32,
+    height: NonZeroU32,
+}
+
+enum Command {
+    CreateWindow(Box<PendingAttributes>, Reply<winit::window::WindowId>),
+    Window(winit::window::WindowId, WindowCommand),
+    Exit,
+}
+
+type Handle = Rc<winit::window::Window>;
+
+struct BackgroundWindow {
+    window: Handle,
+    surface: Option<(Context<Handle>, Surface<Handle, Handle>)>,
+}
+
+impl BackgroundWindow {
+    fn present(&mut self, Frame { pixels, width, height }: Frame) -> std::result::Result<(), String> {
+        let (_, surface) = match self.surface {
+            Some(ref mut surface) => surface,
+            None => {
+                let context = Context::new(self.window.clone()).map_err(|e| format!("failed to create buffer context: {e}"))?;
+                let surface = Surface::new(&context, self.window.clone()).map_err(|e| format!("failed to create buffer surface: {e}"))?;
+                self.surface.insert((context, surface))
+            }
+        };
+
+        surface.resize(width, height).map_err(|e| format!("failed to resize surface: {e}"))?;
+        let mut buffer = surface.buffer_mut().map_err(|e| format!("failed to access buffer: {e}"))?;
+        buffer.copy_from_slice(&pixels);
+        self.window.pre_present_notify();
+        buffer.present().map_err(|e| format!("failed to present buffer: {e}"))
+    }
+}
+
+struct Forwarder {
+    on_event: ThreadsafeNoCallee<FnArgs<(BackgroundEvent,)>, ()>,
+    windows: HashMap<winit::window::WindowId, BackgroundWindow>,
+    registry: WindowRegistryOptions,
+}
+
+impl Forwarder {
+    fn forward(&self, event: BackgroundEvent) {
+        // the queue is unbounded, this only fails once node is shutting down and nobody listens anymore
+        let _ = self.on_event.call(FnArgs::from((event,)), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+
+    fn create_window(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, attrs: PendingAttributes) -> std::result::Result<winit::window::WindowId, String> {
+        let display = event_loop.display_handle().map_err(|e| e.to_string())?.as_raw();
+        let window = event_loop.create_window(attrs.build(&display)).map_err(|e| e.to_string())?;
+        let id = window.id();
+        self.windows.insert(id, BackgroundWindow { window: Rc::new(window), surface: None });
+        Ok(id)
+    }
+
+    /// The registry options of the other runners, without js references to release
+    fn close(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId) {
+        if self.windows.remove(&window_id).is_some()
+            && self.windows.is_empty()
+            && self.registry.exit_on_last_close.unwrap_or(false)
+        {
+            event_loop.exit();
+        }
+    }
+}
+
+impl ApplicationHandler<Command> for Forwarder {
+    fn resumed(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.forward(BackgroundEvent::Resumed);
+    }
+
+    fn user_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, command: Command) {
+        match command {
+            Command::CreateWindow(attrs, reply) => {
+                let _ = reply.send(self.create_window(event_loop, *attrs));
+            }
+            Command::Window(window_id, command) => {
+                let Some(entry) = self.windows.get_mut(&window_id) else {
+                    // dropping the reply tells the caller the window is gone
+                    return;
+                };
+                let window = &entry.window;
+                match command {
+                    WindowCommand::SetTitle(title) => window.set_title(&title),
+                    WindowCommand::SetVisible(visible) => window.set_visible(visible),
+                    WindowCommand::RequestInnerSize(size) => { let _ = window.request_inner_size(size); }
+                    WindowCommand::RequestRedraw => window.request_redraw(),
+                    WindowCommand::SetKeepLogicalSize(keep) => logical_size::set(window, keep),
+                    WindowCommand::RequestActivationToken(reply) => { let _ = reply.send(activation::request(window)); }
+                    WindowCommand::Present(frame, reply) => { let _ = reply.send(entry.present(frame)); }
+                    WindowCommand::Close => self.close(event_loop, window_id),
+                }
+            }
+            Command::Exit => event_loop.exit(),
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId, mut event: winit::event::WindowEvent) {
+        window::before_window_event(window_id, &mut event);
+        let close = match event {
+            winit::event::WindowEvent::CloseRequested => self.registry.close_on_request.unwrap_or(false),
+            winit::event::WindowEvent::Destroyed => true,
+            _ => false,
+        };
+        self.forward(BackgroundEvent::Window { window_id: window_id.into(), event: event.into() });
+        if close {
+            self.close(event_loop, window_id);
+        }
+    }
+
+    fn device_event(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop, device_id: winit::event::DeviceId, event: winit::event::DeviceEvent) {
+        self.forward(BackgroundEvent::Device { device_id: device_id.into(), event: event.into() });
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        for (window_id, event) in drag_drop::take_events() {
+            self.forward(BackgroundEvent::DragDrop { window_id: window_id.into(), event });
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.forward(BackgroundEvent::Suspended);
+    }
+
+    fn exiting(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.windows.clear();
+        self.forward(BackgroundEvent::Exiting);
+    }
+}
+
+/// A winit event loop on its own native thread, windows live on that thread and are driven through
+/// this object, events reach `onEvent` through a threadsafe function.
+///
+/// js gets a `WindowId` instead of a `Window`, frames are drawn with `present`.
+///
+/// X11, Wayland and Windows only, and there can still be only one event loop per process
+#[napi]
+pub struct BackgroundEventLoop {
+    proxy: EventLoopProxy<Command>,
+    /// Ends with the error `run_app` returned
+    thread: Option<JoinHandle<std::result::Result<(), String>>>,
+}
+
+#[napi]
+impl BackgroundEventLoop {
+    #[napi(factory)]
+    pub fn spawn(
+        options: Option<BackgroundEventLoopOptions>,
+        #[napi(ts_arg_type = "(event: BackgroundEvent) => void")]
+        on_event: Function<FnArgs<(BackgroundEvent,)>, ()>,
+    ) -> Result<Self> {
+        let BackgroundEventLoopOptions { backend, app_id, window_registry } = options.unwrap_or_default();
+        let on_event = on_event.build_threadsafe_function().build()?;
+        let (ready, started) = mpsc::channel();
+
+        let thread = std::thread::Builder::new()
+            .name("winit-event-loop".to_owned())
+            .spawn(move || {
+                let mut builder = winit::event_loop::EventLoop::<Command>::with_user_event();
+                let event_loop = configure_builder(&mut builder, backend, Some(true))
+                    .map_err(|e| e.reason)
+                    .and_then(|_| builder.build().map_err(|e| e.to_string()));
+                let event_loop = match event_loop {
+                    Ok(event_loop) => event_loop,
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return Ok(());
+                    }
+                };
+                platform::set_default_app_id(app_id);
+                let _ = ready.send(Ok(event_loop.create_proxy()));
+
+                let mut forwarder = Forwarder { on_event, windows: HashMap::new(), registry: window_registry.unwrap_or_default() };
+                event_loop.run_app(&mut forwarder).map_err(|e| e.to_string())
+            })
+            .map_err(|e| napi_reason!("{e}"))?;
+
+        match started.recv() {
+            Ok(Ok(proxy)) => Ok(Self { proxy, thread: Some(thread) }),
+            Ok(Err(e)) => Err(napi_reason!("{e}")),
+            Err(_) => Err(napi_reason!("the event loop thread stopped before it started")),
+        }
+    }
+
+    /// Blocks until the loop thread created the window
+    #[napi]
+    pub fn create_window(&self, window_attributes: &WindowAttributes) -> Result<WindowId> {
+        self.request(|reply| Command::CreateWindow(Box::new(window_attributes.clone().into()), reply))
+            .map(WindowId::from)
+    }
+
+    /// Draw `pixels` (0RGB, row by row) into the window, blocks until the loop thread presented them.
+    ///
+    /// The surface takes the given size, pass the latest `Resized` size to fill the window
+    #[napi]
+    pub fn present(&self, window_id: &WindowId, pixels: Uint32Array, width: u32, height: u32) -> Result<()> {
+        let (Some(width), Some(height)) = (NonZeroU32::new(width), NonZeroU32::new(height))
+        else { return Err(napi_reason!("invalid frame size [{width}x{height}]")) };
+
+        let expected = width.get() as usize * height.get() as usize;
+        if pixels.len() != expected {
+            return Err(napi_reason!("{} pixels do not fill a {width}x{height} frame of {expected}", pixels.len()));
+        }
+
+        let frame = Frame { pixels: pixels.to_vec(), width, height };
+        self.request(|reply| Command::Window(window_id.0, WindowCommand::Present(frame, reply)))
+    }
+
+    /// See `Window.setKeepLogicalSize`
+    #[napi]
+    pub fn set_keep_logical_size(&self, window_id: &WindowId, keep: bool) -> Result<()> {
+        self.send(Command::Window(window_id.0, WindowCommand::SetKeepLogicalSize(keep)))
+    }
+
+    /// See `Window.requestActivationToken`
+    #[napi]
+    pub async fn request_activation_token(&self, window_id: &WindowId) -> Result<ActivationToken> {
+        let receiver = self.request(|reply| Command::Window(window_id.0, WindowCommand::RequestActivationToken(reply)))?;
+        receiver.await
+            .map(ActivationToken::from)
+            .map_err(|_| napi_reason!("the activation token request was dropped"))
+    }
+
+    #[napi]
+    pub fn set_title(&self, window_id: &WindowId, title: String) -> Result<()> {
+        self.send(Command::Window(window_id.0, WindowCommand::SetTitle(title)))
+    }
+
+    #[napi]
+    pub fn set_visible(&self, window_id: &WindowId, visible: bool) -> Result<()> {
+        self.send(Command::Window(window_id.0, WindowCommand::SetVisible(visible)))
+    }
+
+    #[napi]
+    pub fn request_inner_size(&self, window_id: &WindowId, size: Size) -> Result<()> {
+        self.send(Command::Window(window_id.0, WindowCommand::RequestInnerSize(size.into())))
+    }
+
+    #[napi]
+    pub fn request_redraw(&self, window_id: &WindowId) -> Result<()> {
+        self.send(Command::Window(window_id.0, WindowCommand::RequestRedraw))
+    }
+
+    #[napi]
+    pub fn close_window(&self, window_id: &WindowId) -> Result<()> {
+        self.send(Command::Window(window_id.0, WindowCommand::Close))
+    }
+
+    /// Asks the loop to exit, `onEvent` gets `Exiting` before the thread ends
+    #[napi]
+    pub fn exit(&self) -> Result<()> {
+        self.send(Command::Exit)
+    }
+
+    /// Waits for the loop thread to end, call after `exit`. Throws when winit stopped with an error
+    #[napi]
+    pub fn join(&mut self) -> Result<()> {
+        let Some(thread) = self.thread.take() else { return Ok(()) };
+        thread.join()
+            .map_err(|_| napi_reason!("the event loop thread panicked"))?
+            .map_err(|e| napi_reason!("the event loop stopped with an error: {e}"))
+    }
+
+    #[napi(getter)]
+    pub fn running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    fn send(&self, command: Command) -> Result<()> {
+        self.proxy.send_event(command).map_err(|EventLoopClosed(_)| napi_reason!("the event loop has exited"))
+    }
+
+    /// Send a command and wait for the loop thread to answer it
+    fn request<T>(&self, command: impl FnOnce(Reply<T>) -> Command) -> Result<T> {
+        let (reply, answer) = mpsc::channel();
+        self.send(command(reply))?;
+        match answer.recv_timeout(REPLY_TIMEOUT) {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(e)) => Err(napi_reason!("{e}")),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(napi_reason!("the window is closed or the event loop has exited")),
+            Err(e) => Err(napi_reason!("no answer from the event loop thread: {e}")),
+        }
+    }
+}
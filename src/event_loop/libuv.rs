@@ -0,0 +1,424 @@
+use napi::bindgen_prelude::*;
+
+use crate::{application::public::Application, event_loop::EventLoop};
+
+/// Returned by `EventLoop.runAppOnLibuv`, keeps pumping until winit exits or `stop` is called
+#[napi]
+pub struct LibuvDriver {
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    state: std::rc::Rc<imp::State>,
+}
+
+#[napi]
+impl LibuvDriver {
+    /// Removes the display fd and the deadline timer from libuv, the event loop can be pumped again later
+    #[napi]
+    pub fn stop(&self) {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        self.state.stop();
+    }
+
+    #[napi(getter)]
+    pub fn running(&self) -> bool {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        return !self.state.stopped.get();
+        #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+        false
+    }
+
+    /// Code of `PumpStatus.Exit` once winit exited
+    #[napi(getter)]
+    pub fn exit_code(&self) -> Option<i32> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        return self.state.exit_code.get();
+        #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+        None
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+pub(crate) fn drive(env: Env, event_loop: &mut EventLoop, this: This, app: ClassInstance<Application>) -> Result<LibuvDriver> {
+    imp::State::start(env, event_loop, this, app).map(|state| LibuvDriver { state })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+pub(crate) fn drive(_env: Env, _event_loop: &mut EventLoop, _this: This, _app: ClassInstance<Application>) -> Result<LibuvDriver> {
+    Err(crate::napi_reason!("runAppOnLibuv needs the X11 or Wayland backend, use pumpAppEvents on this platform"))
+}
+
+/// Whether a `LibuvDriver` is running, the event loop can't be run or pumped from js meanwhile
+pub(crate) fn driving() -> bool {
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    return imp::DRIVING.get();
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+    false
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+mod imp {
+    use std::{
+        alloc::{alloc_zeroed, dealloc, Layout},
+        cell::Cell,
+        ffi::{c_int, c_void},
+        os::fd::AsRawFd,
+        ptr,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use napi::{bindgen_prelude::*, check_status, sys};
+    use winit::{
+        application::ApplicationHandler,
+        event_loop::ControlFlow,
+        platform::pump_events::{EventLoopExtPumpEvents, PumpStatus},
+    };
+
+    use crate::{
+        application::{middleware, public::{Application, Runner}, swap::Swappable},
+        event::UserPayload,
+        event_loop::EventLoop,
+        napi_reason,
+        print_err,
+    };
+
+    // `uv_handle_type` values and `uv_poll_event` flags from uv.h
+    const UV_POLL: c_int = 8;
+    const UV_TIMER: c_int = 13;
+    const UV_READABLE: c_int = 1;
+
+    type UvPollCb = unsafe extern "C" fn(handle: *mut c_void, status: c_int, events: c_int);
+    type UvTimerCb = unsafe extern "C" fn(handle: *mut c_void);
+    type UvCloseCb = unsafe extern "C" fn(handle: *mut c_void);
+
+    // resolved against the node binary when the addon is loaded
+    unsafe extern "C" {
+        fn uv_handle_size(kind: c_int) -> usize;
+        fn uv_poll_init(event_loop: *mut c_void, handle: *mut c_void, fd: c_int) -> c_int;
+        fn uv_poll_start(handle: *mut c_void, events: c_int, cb: UvPollCb) -> c_int;
+        fn uv_poll_stop(handle: *mut c_void) -> c_int;
+        fn uv_timer_init(event_loop: *mut c_void, handle: *mut c_void) -> c_int;
+        fn uv_timer_start(handle: *mut c_void, cb: UvTimerCb, timeout: u64, repeat: u64) -> c_int;
+        fn uv_timer_stop(handle: *mut c_void) -> c_int;
+        fn uv_close(handle: *mut c_void, cb: UvCloseCb);
+    }
+
+    thread_local! {
+        pub(super) static DRIVING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// A libuv handle, `data` is the first field of every one of them
+    struct Handle {
+        ptr: *mut c_void,
+        layout: Layout,
+    }
+
+    impl Handle {
+        fn new(kind: c_int, state: *const State) -> Self {
+            let layout = Layout::from_size_align(unsafe { uv_handle_size(kind) }, 16).expect("libuv handle size");
+            let ptr = unsafe { alloc_zeroed(layout) } as *mut c_void;
+            unsafe { *(ptr as *mut *const State) = state };
+            Self { ptr, layout }
+        }
+
+        unsafe fn state<'a>(handle: *mut c_void) -> &'a State {
+            unsafe { &**(handle as *mut *const State) }
+        }
+    }
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.ptr as *mut u8, self.layout) };
+        }
+    }
+
+    pub(crate) struct State {
+        env: sys::napi_env,
+        /// Kept alive by `owners` until the state is dropped
+        event_loop: *mut EventLoop,
+        app: *mut Application<'static>,
+        /// References to the js objects of `event_loop` and `app`
+        owners: [sys::napi_ref; 2],
+        poll: Handle,
+        timer: Handle,
+        resource: sys::napi_ref,
+        context: sys::napi_async_context,
+        pumping: Cell<bool>,
+        stop_requested: Cell<bool>,
+        pub(crate) stopped: Cell<bool>,
+        pub(crate) exit_code: Cell<Option<i32>>,
+        open_handles: Cell<u8>,
+    }
+
+    impl State {
+        pub(crate) fn start(env: Env, event_loop: &mut EventLoop, this: This, mut app: ClassInstance<Application>) -> Result<Rc<Self>> {
+            if DRIVING.get() {
+                return Err(napi_reason!("the event loop is already driven by libuv"));
+            }
+            // their functions only live as long as the call that passed them
+            if let Runner::AsyncFx(_) | Runner::SyncFx(_) = app.runner {
+                return Err(napi_reason!("runAppOnLibuv needs an application made with `withSyncRef`, `withAsyncRef` or `withAsyncFx2Safe`"));
+            }
+            let raw_env = env.raw();
+            let fd = event_loop.inner.as_raw_fd();
+
+            let mut uv_loop = ptr::null_mut();
+            check_status!(unsafe { sys::napi_get_uv_event_loop(raw_env, &mut uv_loop) })?;
+            let uv_loop = uv_loop as *mut c_void;
+
+            // callbacks run outside of any js call, the async context lets node drain promises after each pump
+            let mut resource_object = ptr::null_mut();
+            check_status!(unsafe { sys::napi_create_object(raw_env, &mut resource_object) })?;
+            let mut resource = ptr::null_mut();
+            check_status!(unsafe { sys::napi_create_reference(raw_env, resource_object, 1, &mut resource) })?;
+            let name = "winit";
+            let mut resource_name = ptr::null_mut();
+            check_status!(unsafe { sys::napi_create_string_utf8(raw_env, name.as_ptr().cast(), name.len() as isize, &mut resource_name) })?;
+            let mut context = ptr::null_mut();
+            check_status!(unsafe { sys::napi_async_init(raw_env, resource_object, resource_name, &mut context) })?;
+
+            let mut owners = [ptr::null_mut(); 2];
+            check_status!(unsafe { sys::napi_create_reference(raw_env, this.object.raw(), 1, &mut owners[0]) })?;
+            check_status!(unsafe { sys::napi_create_reference(raw_env, app.value, 1, &mut owners[1]) })?;
+
+            let state = Rc::new_cyclic(|weak| Self {
+                env: raw_env,
+                event_loop,
+                app: (&mut *app as *mut Application).cast(),
+                owners,
+                poll: Handle::new(UV_POLL, weak.as_ptr()),
+                timer: Handle::new(UV_TIMER, weak.as_ptr()),
+                resource,
+                context,
+                pumping: Cell::new(false),
+                stop_requested: Cell::new(false),
+                stopped: Cell::new(false),
+                exit_code: Cell::new(None),
+                open_handles: Cell::new(0),
+            });
+
+            let status = unsafe { uv_poll_init(uv_loop, state.poll.ptr, fd) };
+            if status != 0 {
+                return Err(napi_reason!("uv_poll_init failed for fd {fd}: {status}"));
+            }
+            unsafe { uv_timer_init(uv_loop, state.timer.ptr) };
+            state.open_handles.set(2);
+            // libuv holds its own count until both handles are closed
+            let _ = Rc::into_raw(state.clone());
+            DRIVING.set(true);
+
+            unsafe {
+                uv_poll_start(state.poll.ptr, UV_READABLE, on_readable);
+                // first pump right away, winit sends `NewEvents(Init)` and `resumed` from it
+                uv_timer_start(state.timer.ptr, on_deadline, 0, 0);
+            }
+            Ok(state)
+        }
+
+        pub(crate) fn stop(&self) {
+            if self.pumping.get() {
+                self.stop_requested.set(true);
+            } else {
+                self.close();
+            }
+        }
+
+        fn close(&self) {
+            if self.stopped.replace(true) {
+                return;
+            }
+            DRIVING.set(false);
+            unsafe {
+                uv_poll_stop(self.poll.ptr);
+                uv_timer_stop(self.timer.ptr);
+                uv_close(self.poll.ptr, on_close);
+                uv_close(self.timer.ptr, on_close);
+            }
+        }
+
+        fn pump(&self) {
+            if self.stopped.get() || self.pumping.replace(true) {
+                return;
+            }
+            let (status, control_flow) = self.with_callback_scope(|| {
+                let event_loop = unsafe { &mut *self.event_loop };
+                let app = unsafe { &mut *self.app };
+                pump_once(event_loop, app)
+            });
+            self.pumping.set(false);
+
+            match status {
+                Some(PumpStatus::Exit(code)) => {
+                    self.exit_code.set(Some(code));
+                    self.close();
+                }
+                _ if self.stop_requested.get() => self.close(),
+                Some(PumpStatus::Continue) => self.schedule(control_flow),
+                None => self.close(),
+            }
+        }
+
+        fn schedule(&self, control_flow: ControlFlow) {
+            let timeout = match control_flow {
+                ControlFlow::Poll => Some(0),
+                ControlFlow::Wait => None,
+                ControlFlow::WaitUntil(deadline) => {
+                    let wait = deadline.saturating_duration_since(Instant::now());
+                    Some(wait.as_micros().div_ceil(1000) as u64)
+                }
+            };
+            unsafe {
+                uv_timer_stop(self.timer.ptr);
+                if let Some(timeout) = timeout {
+                    uv_timer_start(self.timer.ptr, on_deadline, timeout, 0);
+                }
+            }
+        }
+
+        fn with_callback_scope(&self, f: impl FnOnce() -> (PumpStatus, ControlFlow)) -> (Option<PumpStatus>, ControlFlow) {
+            let env = self.env;
+            let mut handle_scope = ptr::null_mut();
+            let mut callback_scope = ptr::null_mut();
+            let opened = unsafe {
+                let mut resource_object = ptr::null_mut();
+                sys::napi_open_handle_scope(env, &mut handle_scope) == sys::Status::napi_ok
+                    && sys::napi_get_reference_value(env, self.resource, &mut resource_object) == sys::Status::napi_ok
+                    && sys::napi_open_callback_scope(env, resource_object, self.context, &mut callback_scope) == sys::Status::napi_ok
+            };
+            if !opened {
+                let err = napi_reason!("can't enter node from the libuv callback");
+                print_err!(err);
+                return (None, ControlFlow::Wait);
+            }
+
+            let (status, control_flow) = f();
+
+            unsafe {
+                // runs queued microtasks
+                sys::napi_close_callback_scope(env, callback_scope);
+                let mut pending = false;
+                sys::napi_is_exception_pending(env, &mut pending);
+                if pending {
+                    let mut exception = ptr::null_mut();
+                    sys::napi_get_and_clear_last_exception(env, &mut exception);
+                    if let Ok(exception) = Unknown::from_napi_value(env, exception) {
+                        let err = Error::from(exception);
+                        print_err!(err);
+                    }
+                }
+                sys::napi_close_handle_scope(env, handle_scope);
+            }
+            (Some(status), control_flow)
+        }
+    }
+
+    impl Drop for State {
+        fn drop(&mut self) {
+            unsafe {
+                sys::napi_async_destroy(self.env, self.context);
+                sys::napi_delete_reference(self.env, self.resource);
+                for owner in self.owners {
+                    sys::napi_delete_reference(self.env, owner);
+                }
+            }
+        }
+    }
+
+    unsafe extern "C" fn on_readable(handle: *mut c_void, status: c_int, _events: c_int) {
+        let state = unsafe { Handle::state(handle) };
+        if status < 0 {
+            let err = napi_reason!("polling the display fd failed: {status}");
+            print_err!(err);
+            state.close();
+            return;
+        }
+        state.pump();
+    }
+
+    unsafe extern "C" fn on_deadline(handle: *mut c_void) {
+        unsafe { Handle::state(handle) }.pump();
+    }
+
+    unsafe extern "C" fn on_close(handle: *mut c_void) {
+        let state = unsafe { Handle::state(handle) };
+        let open = state.open_handles.get() - 1;
+        state.open_handles.set(open);
+        if open == 0 {
+            // the count `start` leaked for libuv
+            drop(unsafe { Rc::from_raw(state as *const State) });
+        }
+    }
+
+    /// Dispatches whatever winit has ready without blocking
+    fn pump_once(event_loop: &mut EventLoop, app: &mut Application) -> (PumpStatus, ControlFlow) {
+        let _layers = middleware::Installed::new(&app.layers);
+        let mut control_flow = ControlFlow::Wait;
+        let timeout = Some(Duration::ZERO);
+        let inner = &mut event_loop.inner;
+
+        let status = match app.runner {
+            Runner::AsyncFx(ref mut handler) => inner.pump_app_events(timeout, &mut Recording::new(handler, &mut control_flow)),
+            Runner::SyncFx(ref mut handler) => inner.pump_app_events(timeout, &mut Recording::new(handler, &mut control_flow)),
+            Runner::AsyncRef(ref mut handler) => inner.pump_app_events(timeout, &mut Recording::new(&mut Swappable::new(handler, &app.pending), &mut control_flow)),
+            Runner::SyncRef(ref mut handler) => inner.pump_app_events(timeout, &mut Recording::new(&mut Swappable::new(handler, &app.pending), &mut control_flow)),
+            Runner::SafeCall(ref mut handler) => inner.pump_app_events(timeout, &mut Recording::new(handler, &mut control_flow)),
+        };
+        (status, control_flow)
+    }
+
+    /// Keeps the control flow the application asked for, `EventLoop` has no getter for it
+    struct Recording<'a> {
+        handler: &'a mut dyn ApplicationHandler<UserPayload>,
+        control_flow: &'a mut ControlFlow,
+    }
+
+    impl<'a> Recording<'a> {
+        fn new(handler: &'a mut dyn ApplicationHandler<UserPayload>, control_flow: &'a mut ControlFlow) -> Self {
+            Self { handler, control_flow }
+        }
+    }
+
+    macro_rules! record {
+        ($self: ident, $event_loop: ident . $call: ident ( $($args: expr), * )) => {{
+            $self.handler.$call($event_loop $(, $args)*);
+            *$self.control_flow = $event_loop.control_flow();
+        }};
+    }
+
+    impl ApplicationHandler<UserPayload> for Recording<'_> {
+        fn new_events(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, cause: winit::event::StartCause) {
+            record!(self, event_loop.new_events(cause))
+        }
+
+        fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+            record!(self, event_loop.resumed())
+        }
+
+        fn user_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, event: UserPayload) {
+            record!(self, event_loop.user_event(event))
+        }
+
+        fn window_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, window_id: winit::window::WindowId, event: winit::event::WindowEvent) {
+            record!(self, event_loop.window_event(window_id, event))
+        }
+
+        fn device_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, device_id: winit::event::DeviceId, event: winit::event::DeviceEvent) {
+            record!(self, event_loop.device_event(device_id, event))
+        }
+
+        fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+            record!(self, event_loop.about_to_wait())
+        }
+
+        fn suspended(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+            record!(self, event_loop.suspended())
+        }
+
+        fn exiting(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+            record!(self, event_loop.exiting())
+        }
+
+        fn memory_warning(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+            record!(self, event_loop.memory_warning())
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+
+use arboard::{Error as ArboardError, ImageData};
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+use arboard::{ClearExtLinux, GetExtLinux, LinuxClipboardKind, SetExtLinux};
+
+use namespace::ClipboardKind;
+
+/// Empty clipboards and missing formats are `null` in js, not errors
+fn content<T>(result: Result<T, ArboardError>) -> Result<Option<T>, String> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(ArboardError::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+macro_rules! with_kind {
+    ($builder: expr, $kind: expr) => {{
+        let builder = $builder;
+        match $kind {
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            ClipboardKind::Clipboard => builder.clipboard(LinuxClipboardKind::Clipboard),
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            ClipboardKind::Primary => builder.clipboard(LinuxClipboardKind::Primary),
+            #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+            ClipboardKind::Clipboard => builder,
+            #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+            ClipboardKind::Primary => return Err("the primary selection only exists on X11 and Wayland".to_owned()),
+        }
+    }};
+}
+
+/// Wayland text goes through the event loop's connection, everything else through arboard,
+/// see "Display Server Support" in the readme
+enum Backend {
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    Wayland(smithay_clipboard::Clipboard),
+    Arboard,
+}
+
+pub(crate) struct ClipboardInner {
+    backend: Backend,
+    /// Created on first use, on wayland only needed for html and images
+    arboard: Option<arboard::Clipboard>,
+}
+
+impl ClipboardInner {
+    fn new(display: &rwh_06::RawDisplayHandle) -> Self {
+        let backend = match display {
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            rwh_06::RawDisplayHandle::Wayland(handle) => {
+                // the display stays alive as long as the `OwnedDisplayHandle` kept next to this
+                Backend::Wayland(unsafe { smithay_clipboard::Clipboard::new(handle.display.as_ptr()) })
+            }
+            _ => Backend::Arboard,
+        };
+        Self { backend, arboard: None }
+    }
+
+    fn arboard(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.arboard.is_none() {
+            self.arboard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        Ok(self.arboard.as_mut().unwrap())
+    }
+
+    fn get_text(&mut self, kind: ClipboardKind) -> Result<Option<String>, String> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        if let Backend::Wayland(clipboard) = &self.backend {
+            let result = match kind {
+                ClipboardKind::Clipboard => clipboard.load(),
+                ClipboardKind::Primary => clipboard.load_primary(),
+            };
+            // smithay reports an empty clipboard as an io error
+            return Ok(result.ok());
+        }
+        content(with_kind!(self.arboard()?.get(), kind).text())
+    }
+
+    fn set_text(&mut self, text: String, kind: ClipboardKind) -> Result<(), String> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        if let Backend::Wayland(clipboard) = &self.backend {
+            match kind {
+                ClipboardKind::Clipboard => clipboard.store(text),
+                ClipboardKind::Primary => clipboard.store_primary(text),
+            }
+            return Ok(());
+        }
+        with_kind!(self.arboard()?.set(), kind).text(text).map_err(|e| e.to_string())
+    }
+
+    fn get_html(&mut self, kind: ClipboardKind) -> Result<Option<String>, String> {
+        content(with_kind!(self.arboard()?.get(), kind).html())
+    }
+
+    fn set_html(&mut self, html: String, alt_text: Option<String>, kind: ClipboardKind) -> Result<(), String> {
+        with_kind!(self.arboard()?.set(), kind).html(html, alt_text).map_err(|e| e.to_string())
+    }
+
+    fn get_image(&mut self, kind: ClipboardKind) -> Result<Option<ImageData<'static>>, String> {
+        content(with_kind!(self.arboard()?.get(), kind).image())
+    }
+
+    fn set_image(&mut self, image: ImageData<'_>, kind: ClipboardKind) -> Result<(), String> {
+        with_kind!(self.arboard()?.set(), kind).image(image).map_err(|e| e.to_string())
+    }
+
+    fn clear(&mut self, kind: ClipboardKind) -> Result<(), String> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        if let Backend::Wayland(clipboard) = &self.backend {
+            match kind {
+                ClipboardKind::Clipboard => clipboard.store(""),
+                ClipboardKind::Primary => clipboard.store_primary(""),
+            }
+            return Ok(());
+        }
+        match kind {
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            ClipboardKind::Primary => self.arboard()?.clear_with().clipboard(LinuxClipboardKind::Primary),
+            #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+            ClipboardKind::Primary => return Err("the primary selection only exists on X11 and Wayland".to_owned()),
+            ClipboardKind::Clipboard => self.arboard()?.clear(),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[napi(js_name = "Extra")]
+pub mod namespace {
+    use super::*;
+    use napi::bindgen_prelude::*;
+    use rwh_06::HasDisplayHandle;
+    use crate::{event_loop::OwnedDisplayHandle, napi_reason};
+
+    #[napi(string_enum)]
+    #[derive(Clone, Copy, Default)]
+    pub enum ClipboardKind {
+        #[default]
+        Clipboard,
+        /// Selection pasted with the middle mouse button, X11 and Wayland only
+        Primary,
+    }
+
+    #[napi(object)]
+    pub struct ClipboardImage {
+        pub width: u32,
+        pub height: u32,
+        /// RGBA, 4 bytes per pixel
+        pub rgba: Buffer,
+    }
+
+    /// Text, html and RGBA images on the clipboard and, on X11 and Wayland, the primary selection.
+    ///
+    /// Only wayland text shares the event loop's display connection, everything else opens a
+    /// second one to the same display
+    #[napi]
+    pub struct Clipboard {
+        pub(crate) inner: ClipboardInner,
+        /// keeps the wayland connection alive, must be dropped after `inner`
+        pub(crate) display: winit::event_loop::OwnedDisplayHandle,
+    }
+
+    #[napi]
+    impl Clipboard {
+        /// Use the display connection of the event loop, see `EventLoop.ownedDisplayHandle` and `ActiveEventLoop.ownedDisplayHandle`
+        #[napi(constructor)]
+        pub fn new(display: &OwnedDisplayHandle) -> Result<Self> {
+            let display = display.0.clone();
+            let raw = display.display_handle()
+                .map_err(|e| napi_reason!("{e}"))?
+                .as_raw();
+            Ok(Self { inner: ClipboardInner::new(&raw), display })
+        }
+
+        #[napi]
+        pub fn get_text(&mut self, kind: Option<ClipboardKind>) -> Result<Option<String>> {
+            self.inner.get_text(kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))
+        }
+
+        #[napi]
+        pub fn set_text(&mut self, text: String, kind: Option<ClipboardKind>) -> Result<()> {
+            self.inner.set_text(text, kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))
+        }
+
+        #[napi]
+        pub fn get_html(&mut self, kind: Option<ClipboardKind>) -> Result<Option<String>> {
+            self.inner.get_html(kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))
+        }
+
+        /// `altText` is offered to apps that can't paste html
+        #[napi]
+        pub fn set_html(&mut self, html: String, alt_text: Option<String>, kind: Option<ClipboardKind>) -> Result<()> {
+            self.inner.set_html(html, alt_text, kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))
+        }
+
+        #[napi]
+        pub fn get_image(&mut self, kind: Option<ClipboardKind>) -> Result<Option<ClipboardImage>> {
+            let image = self.inner.get_image(kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))?;
+            Ok(image.map(|ImageData { width, height, bytes }| ClipboardImage {
+                width: width as u32,
+                height: height as u32,
+                rgba: Buffer::from(bytes.into_owned()),
+            }))
+        }
+
+        #[napi]
+        pub fn set_image(&mut self, image: ClipboardImage, kind: Option<ClipboardKind>) -> Result<()> {
+            let ClipboardImage { width, height, rgba } = image;
+            let expected = width as usize * height as usize * 4;
+            if rgba.len() != expected {
+                return Err(napi_reason!("rgba length ({}) does not match {width}x{height}", rgba.len()));
+            }
+            let image = ImageData { width: width as usize, height: height as usize, bytes: Cow::Borrowed(rgba.as_ref()) };
+            self.inner.set_image(image, kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))
+        }
+
+        #[napi]
+        pub fn clear(&mut self, kind: Option<ClipboardKind>) -> Result<()> {
+            self.inner.clear(kind.unwrap_or_default()).map_err(|e| napi_reason!("{e}"))
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Condvar, Mutex, OnceLock, Weak,
+    },
+};
+use crate::{extra::memory::Chunk, utils::alias::ThreadsafeNoCallee};
+
+#[derive(Clone, Copy, PartialEq)]
+enum SlotState {
+    Free,
+    /// held by a writer until it is submitted
+    Writing,
+    /// completed, carries the submit sequence so the newest one wins
+    Ready(u64),
+    /// last frame copied to the surface, kept to re-present on expose
+    Displayed,
+}
+
+struct Slot {
+    chunk: Arc<Chunk>,
+    state: SlotState,
+    generation: u32,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Counters {
+    submitted: u64,
+    presented: u64,
+    dropped: u64,
+    stale: u64,
+    timeouts: u64,
+}
+
+struct QueueState {
+    width: u32,
+    height: u32,
+    slots: Vec<Slot>,
+    sequence: u64,
+    counters: Counters,
+}
+
+pub(crate) struct SharedQueue {
+    state: Mutex<QueueState>,
+    freed: Condvar,
+    on_submit: Option<ThreadsafeNoCallee<(), ()>>,
+}
+
+static NEXT_GENERATION: AtomicU32 = AtomicU32::new(1);
+static NEXT_QUEUE_ID: AtomicU32 = AtomicU32::new(1);
+static QUEUES: OnceLock<Mutex<HashMap<u32, Weak<SharedQueue>>>> = OnceLock::new();
+
+fn queues() -> &'static Mutex<HashMap<u32, Weak<SharedQueue>>> {
+    QUEUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn alloc_slots(width: u32, height: u32, count: usize) -> Option<Vec<Slot>> {
+    let size = (width as usize * height as usize * 4).max(4);
+    (0..count)
+        .map(|_| Chunk::new(size).map(|chunk| Slot { chunk: Arc::new(chunk), state: SlotState::Free, generation: 0 }))
+        .collect()
+}
+
+/// Pixels of a frame chosen for presentation, valid until the next `take_frame`
+pub(crate) struct PresentFrame {
+    pub(crate) chunk: Arc<Chunk>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl PresentFrame {
+    pub(crate) fn pixels(&self) -> &[u32] {
+        let len = self.width as usize * self.height as usize;
+        unsafe { std::slice::from_raw_parts(self.chunk.as_ptr().cast::<u32>(), len) }
+    }
+}
+
+impl SharedQueue {
+    /// Newest completed frame, or the one on screen when nothing new was submitted
+    pub(crate) fn take_frame(&self) -> Option<(PresentFrame, bool)> {
+        let mut state = self.state.lock().unwrap();
+        let QueueState { width, height, .. } = *state;
+
+        let newest = state.slots.iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot.state {
+                SlotState::Ready(sequence) => Some((index, sequence)),
+                _ => None,
+            })
+            .max_by_key(|(_, sequence)| *sequence)
+            .map(|(index, _)| index);
+
+        let fresh = newest.is_some();
+        if let Some(newest) = newest {
+            for slot in state.slots.iter_mut() {
+                if slot.state == SlotState::Displayed {
+                    slot.state = SlotState::Free;
+                }
+            }
+            state.slots[newest].state = SlotState::Displayed;
+            state.counters.presented += 1;
+            self.freed.notify_all();
+        }
+
+        state.slots.iter()
+            .find(|slot| slot.state == SlotState::Displayed)
+            .map(|slot| (PresentFrame { chunk: slot.chunk.clone(), width, height }, fresh))
+    }
+}
+
+#[napi(js_name = "Extra")]
+pub mod namespace {
+    use super::*;
+    use crate::napi_reason;
+    use napi::bindgen_prelude::*;
+    use napi::threadsafe_function::ThreadsafeFunctionCallMode;
+    use std::time::Duration;
+
+    #[napi(object, object_to_js = false)]
+    pub struct FrameQueueOptions<'scope> {
+        /// 2 for double buffering, 3 (default) for triple buffering
+        pub buffers: Option<u32>,
+        /// Called on the creating thread after each submit, typically to `requestRedraw`
+        #[napi(ts_type = "() => void")]
+        pub on_submit: Option<Function<'scope, (), ()>>,
+    }
+
+    /// Frame handed to a writer by `FrameQueue.acquire`
+    #[napi(object)]
+    pub struct FrameSlot {
+        pub index: u32,
+        pub generation: u32,
+        pub width: u32,
+        pub height: u32,
+        pub view: Uint32Array,
+    }
+
+    #[napi(object)]
+    pub struct FrameQueueStats {
+        pub submitted: f64,
+        pub presented: f64,
+        /// completed frames replaced by a newer one before being presented
+        pub dropped: f64,
+        /// frames submitted after a resize invalidated them
+        pub stale: f64,
+        /// acquires that gave up waiting for a free buffer
+        pub timeouts: f64,
+    }
+
+    /// Frames rendered off the main thread, presented by `BufferSurface.presentFromQueue`.
+    ///
+    /// Create it on the main thread and pass `id` to a worker, which opens it with `FrameQueue.attach`.
+    #[napi]
+    pub struct FrameQueue {
+        pub(crate) id: u32,
+        pub(crate) shared: Arc<SharedQueue>,
+    }
+
+    #[napi]
+    impl FrameQueue {
+        #[napi(constructor)]
+        pub fn new(width: u32, height: u32, options: Option<FrameQueueOptions<'_>>) -> Result<Self> {
+            let (buffers, on_submit) = match options {
+                Some(FrameQueueOptions { buffers, on_submit }) => (buffers.unwrap_or(3), on_submit),
+                None => (3, None),
+            };
+
+            if !(2..=3).contains(&buffers) {
+                return Err(napi_reason!("buffers must be 2 or 3, got {buffers}"));
+            }
+
+            let on_submit = match on_submit {
+                Some(callback) => Some(callback.build_threadsafe_function().build()?),
+                None => None,
+            };
+
+            let slots = alloc_slots(width, height, buffers as usize)
+                .ok_or_else(|| napi_reason!("failed to allocate frame buffers for {width}x{height}"))?;
+
+            let shared = Arc::new(SharedQueue {
+                state: Mutex::new(QueueState { width, height, slots, sequence: 0, counters: Counters::default() }),
+                freed: Condvar::new(),
+                on_submit,
+            });
+
+            let id = NEXT_QUEUE_ID.fetch_add(1, Ordering::Relaxed);
+            queues().lock().unwrap().insert(id, Arc::downgrade(&shared));
+
+            Ok(Self { id, shared })
+        }
+
+        /// Open a queue created on another thread
+        #[napi(factory)]
+        pub fn attach(id: u32) -> Result<Self> {
+            let shared = queues().lock().unwrap()
+                .get(&id)
+                .and_then(Weak::upgrade)
+                .ok_or_else(|| napi_reason!("no frame queue with id {id}"))?;
+            Ok(Self { id, shared })
+        }
+
+        #[napi(getter)]
+        pub fn id(&self) -> u32 {
+            self.id
+        }
+
+        #[napi(getter)]
+        pub fn width(&self) -> u32 {
+            self.shared.state.lock().unwrap().width
+        }
+
+        #[napi(getter)]
+        pub fn height(&self) -> u32 {
+            self.shared.state.lock().unwrap().height
+        }
+
+        /// Whether a submitted frame is waiting to be presented
+        #[napi(getter)]
+        pub fn has_new_frame(&self) -> bool {
+            let state = self.shared.state.lock().unwrap();
+            state.slots.iter().any(|slot| matches!(slot.state, SlotState::Ready(_)))
+        }
+
+        /// Take a free buffer to render into, waiting up to `timeoutMillis` (default 0) for one.
+        ///
+        /// Only wait from a worker, the main thread is the one freeing buffers.
+        #[napi]
+        pub fn acquire(&self, timeout_millis: Option<f64>) -> Option<FrameSlot> {
+            let timeout = Duration::from_millis(timeout_millis.unwrap_or(0.).max(0.) as u64);
+            let state = self.shared.state.lock().unwrap();
+
+            let (mut state, _) = self.shared.freed
+                .wait_timeout_while(state, timeout, |state| {
+                    state.slots.iter().all(|slot| slot.state != SlotState::Free)
+                })
+                .unwrap();
+
+            let QueueState { width, height, .. } = *state;
+            let Some((index, slot)) = state.slots.iter_mut()
+                .enumerate()
+                .find(|(_, slot)| slot.state == SlotState::Free)
+            else {
+                state.counters.timeouts += 1;
+                return None;
+            };
+
+            slot.state = SlotState::Writing;
+            slot.generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+
+            let chunk = slot.chunk.clone();
+            let generation = slot.generation;
+            let len = width as usize * height as usize;
+            let ptr = chunk.as_ptr().cast::<u32>();
+            let view = unsafe { Uint32Array::with_external_data(ptr, len, move |_, _| drop(chunk)) };
+
+            Some(FrameSlot { index: index as u32, generation, width, height, view })
+        }
+
+        /// Mark a frame as complete, replacing any completed frame not yet presented
+        #[napi]
+        pub fn submit(&self, frame: FrameSlot) -> Result<()> {
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                let index = frame.index as usize;
+
+                let valid = matches!(
+                    state.slots.get(index),
+                    Some(slot) if slot.state == SlotState::Writing && slot.generation == frame.generation
+                );
+                if !valid {
+                    state.counters.stale += 1;
+                    return Ok(());
+                }
+
+                let mut dropped = 0;
+                for slot in state.slots.iter_mut() {
+                    if matches!(slot.state, SlotState::Ready(_)) {
+                        slot.state = SlotState::Free;
+                        dropped += 1;
+                    }
+                }
+
+                state.sequence += 1;
+                let sequence = state.sequence;
+                state.slots[index].state = SlotState::Ready(sequence);
+                state.counters.submitted += 1;
+                state.counters.dropped += dropped;
+            }
+            self.shared.freed.notify_all();
+
+            if let Some(on_submit) = &self.shared.on_submit {
+                let status = on_submit.call((), ThreadsafeFunctionCallMode::NonBlocking);
+                if Status::Ok != status {
+                    return Err(napi_reason!("the frame was submitted but onSubmit could not be queued: {status}"));
+                }
+            }
+            Ok(())
+        }
+
+        /// Give back an acquired frame without submitting it
+        #[napi]
+        pub fn discard(&self, frame: FrameSlot) {
+            let mut state = self.shared.state.lock().unwrap();
+            if let Some(slot) = state.slots.get_mut(frame.index as usize)
+                && slot.state == SlotState::Writing
+                && slot.generation == frame.generation
+            {
+                slot.state = SlotState::Free;
+            }
+            drop(state);
+            self.shared.freed.notify_all();
+        }
+
+        /// Reallocate every buffer, frames acquired before the resize become stale
+        #[napi]
+        pub fn resize(&self, width: u32, height: u32) -> Result<()> {
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                if state.width == width && state.height == height {
+                    return Ok(());
+                }
+                let slots = alloc_slots(width, height, state.slots.len())
+                    .ok_or_else(|| napi_reason!("failed to allocate frame buffers for {width}x{height}"))?;
+                state.slots = slots;
+                state.width = width;
+                state.height = height;
+            }
+            self.shared.freed.notify_all();
+            Ok(())
+        }
+
+        #[napi]
+        pub fn stats(&self) -> FrameQueueStats {
+            let Counters { submitted, presented, dropped, stale, timeouts } = self.shared.state.lock().unwrap().counters;
+            FrameQueueStats {
+                submitted: submitted as f64,
+                presented: presented as f64,
+                dropped: dropped as f64,
+                stale: stale as f64,
+                timeouts: timeouts as f64,
+            }
+        }
+    }
+}
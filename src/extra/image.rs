@@ -0,0 +1,297 @@
+use ::image::{codecs::png::PngEncoder, ExtendedColorType, ImageEncoder};
+
+#[inline]
+fn pack_rgb(r: u8, g: u8, b: u8) -> u32 {
+    ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+}
+
+#[inline]
+fn unpack_rgb(pixel: u32) -> [u8; 3] {
+    [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]
+}
+
+/// rgb in the lower 24 bits, alpha in the upper 8 bits
+#[inline]
+fn with_alpha(pixel: u32, alpha: u8) -> u32 {
+    (pixel & 0x00FF_FFFF) | ((alpha as u32) << 24)
+}
+
+#[inline]
+fn blend(src: u32, dst: u32) -> u32 {
+    let alpha = src >> 24;
+    match alpha {
+        0xFF => src & 0x00FF_FFFF,
+        0 => dst,
+        _ => {
+            let [sr, sg, sb] = unpack_rgb(src);
+            let [dr, dg, db] = unpack_rgb(dst);
+            let mix = |s: u8, d: u8| ((s as u32 * alpha + d as u32 * (255 - alpha) + 127) / 255) as u8;
+            pack_rgb(mix(sr, dr), mix(sg, dg), mix(sb, db))
+        }
+    }
+}
+
+pub(crate) fn encode_png_raw(pixels: &[u32], alpha: Option<&[u8]>, width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let expected = width as usize * height as usize;
+    if pixels.len() != expected {
+        return Err(format!("pixel length ({}) does not match {width}x{height}", pixels.len()));
+    }
+
+    let (raw, color_type) = match alpha {
+        Some(alpha) => {
+            let raw = pixels.iter().zip(alpha)
+                .flat_map(|(pixel, a)| {
+                    let [r, g, b] = unpack_rgb(*pixel);
+                    [r, g, b, *a]
+                })
+                .collect::<Vec<u8>>();
+            (raw, ExtendedColorType::Rgba8)
+        }
+        None => {
+            let raw = pixels.iter().flat_map(|pixel| unpack_rgb(*pixel)).collect::<Vec<u8>>();
+            (raw, ExtendedColorType::Rgb8)
+        }
+    };
+
+    let mut out = Vec::new();
+    PngEncoder::new(&mut out)
+        .write_image(&raw, width, height, color_type)
+        .map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+#[napi(js_name = "Extra")]
+pub mod namespace {
+    use super::*;
+    use crate::napi_reason;
+    use napi::bindgen_prelude::*;
+
+    #[napi(string_enum)]
+    #[derive(Clone, Copy, Default)]
+    pub enum ImageFilter {
+        #[default]
+        Nearest,
+        Bilinear,
+    }
+
+    /// Destination rectangle of a blit, defaults to the image size at the origin
+    #[napi(object)]
+    #[derive(Clone, Default)]
+    pub struct BlitOptions {
+        pub x: Option<i32>,
+        pub y: Option<i32>,
+        pub width: Option<u32>,
+        pub height: Option<u32>,
+        pub filter: Option<ImageFilter>,
+    }
+
+    /// Decoded image held natively in the 0RGB format used by `BufferSurface`
+    #[napi]
+    #[derive(Clone)]
+    pub struct Image {
+        pub(crate) width: u32,
+        pub(crate) height: u32,
+        pub(crate) pixels: Vec<u32>,
+        /// `None` when every pixel is opaque
+        pub(crate) alpha: Option<Vec<u8>>,
+    }
+
+    #[napi]
+    impl Image {
+        /// Decode a PNG, JPEG or BMP file from memory
+        #[napi(factory)]
+        pub fn decode(data: Uint8Array) -> Result<Self> {
+            let decoded = ::image::load_from_memory(data.as_ref())
+                .map_err(|e| napi_reason!("failed to decode image: {e}"))?
+                .into_rgba8();
+
+            let (width, height) = decoded.dimensions();
+            let mut pixels = Vec::with_capacity(width as usize * height as usize);
+            let mut alpha = Vec::with_capacity(width as usize * height as usize);
+
+            for [r, g, b, a] in decoded.pixels().map(|p| p.0) {
+                pixels.push(pack_rgb(r, g, b));
+                alpha.push(a);
+            }
+
+            let alpha = if alpha.iter().all(|a| *a == 0xFF) { None } else { Some(alpha) };
+
+            Ok(Self { width, height, pixels, alpha })
+        }
+
+        #[napi(factory)]
+        pub fn from_pixels(pixels: Uint32Array, width: u32, height: u32) -> Result<Self> {
+            let expected = width as usize * height as usize;
+            if pixels.len() != expected {
+                return Err(napi_reason!("pixel length ({}) does not match {width}x{height}", pixels.len()));
+            }
+            let pixels = pixels.iter().map(|pixel| pixel & 0x00FF_FFFF).collect();
+            Ok(Self { width, height, pixels, alpha: None })
+        }
+
+        #[napi(getter)]
+        pub fn width(&self) -> u32 {
+            self.width
+        }
+
+        #[napi(getter)]
+        pub fn height(&self) -> u32 {
+            self.height
+        }
+
+        #[napi(getter)]
+        pub fn has_alpha(&self) -> bool {
+            self.alpha.is_some()
+        }
+
+        /// Copy of the 0RGB pixels
+        #[napi]
+        pub fn to_pixels(&self) -> Uint32Array {
+            Uint32Array::new(self.pixels.clone())
+        }
+
+        /// Draw into a framebuffer, scaled to the destination rectangle and alpha blended
+        #[napi]
+        pub fn blit_to(&self, mut view: Uint32Array, width: u32, height: u32, options: Option<BlitOptions>) -> Result<()> {
+            let expected = width as usize * height as usize;
+            if view.len() != expected {
+                return Err(napi_reason!("view length ({}) does not match {width}x{height}", view.len()));
+            }
+            // SAFETY: the view is only borrowed for this synchronous call, no js runs while it is written
+            let pixels = unsafe { view.as_mut() };
+            self.blit(pixels, width, height, &options.unwrap_or_default());
+            Ok(())
+        }
+
+        #[napi]
+        pub fn encode_png(&self) -> Result<Buffer> {
+            encode_png_raw(&self.pixels, self.alpha.as_deref(), self.width, self.height)
+                .map(Buffer::from)
+                .map_err(|e| napi_reason!("failed to encode png: {e}"))
+        }
+    }
+
+    impl Image {
+        #[inline]
+        fn texel(&self, x: u32, y: u32) -> u32 {
+            let idx = (y * self.width + x) as usize;
+            let alpha = self.alpha.as_ref().map(|alpha| alpha[idx]).unwrap_or(0xFF);
+            with_alpha(self.pixels[idx], alpha)
+        }
+
+        fn sample_nearest(&self, u: f64, v: f64) -> u32 {
+            let x = (u.floor() as i64).clamp(0, self.width as i64 - 1) as u32;
+            let y = (v.floor() as i64).clamp(0, self.height as i64 - 1) as u32;
+            self.texel(x, y)
+        }
+
+        fn sample_bilinear(&self, u: f64, v: f64) -> u32 {
+            let u = (u - 0.5).clamp(0., (self.width - 1) as f64);
+            let v = (v - 0.5).clamp(0., (self.height - 1) as f64);
+            let (x0, y0) = (u.floor() as u32, v.floor() as u32);
+            let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+            let (fx, fy) = (u - x0 as f64, v - y0 as f64);
+
+            let corners = [self.texel(x0, y0), self.texel(x1, y0), self.texel(x0, y1), self.texel(x1, y1)];
+            let weights = [(1. - fx) * (1. - fy), fx * (1. - fy), (1. - fx) * fy, fx * fy];
+
+            let channel = |shift: u32| {
+                let sum: f64 = corners.iter().zip(weights)
+                    .map(|(pixel, weight)| ((pixel >> shift) & 0xFF) as f64 * weight)
+                    .sum();
+                (sum.round() as u32).min(0xFF)
+            };
+
+            (channel(24) << 24) | (channel(16) << 16) | (channel(8) << 8) | channel(0)
+        }
+
+        pub(crate) fn blit(&self, dest: &mut [u32], dest_width: u32, dest_height: u32, options: &BlitOptions) {
+            if self.width == 0 || self.height == 0 {
+                return;
+            }
+
+            let x = options.x.unwrap_or(0) as i64;
+            let y = options.y.unwrap_or(0) as i64;
+            let width = options.width.unwrap_or(self.width) as i64;
+            let height = options.height.unwrap_or(self.height) as i64;
+            if width == 0 || height == 0 {
+                return;
+            }
+
+            let scale_x = self.width as f64 / width as f64;
+            let scale_y = self.height as f64 / height as f64;
+            let filter = options.filter.unwrap_or_default();
+
+            let (left, right) = (x.max(0), (x + width).min(dest_width as i64));
+            let (top, bottom) = (y.max(0), (y + height).min(dest_height as i64));
+
+            for dy in top..bottom {
+                let v = (dy - y) as f64 * scale_y;
+                let row = (dy * dest_width as i64) as usize;
+                for dx in left..right {
+                    let u = (dx - x) as f64 * scale_x;
+                    let src = match filter {
+                        ImageFilter::Nearest => self.sample_nearest(u, v),
+                        ImageFilter::Bilinear => self.sample_bilinear(u + 0.5 * scale_x, v + 0.5 * scale_y),
+                    };
+                    let idx = row + dx as usize;
+                    dest[idx] = blend(src, dest[idx]);
+                }
+            }
+        }
+    }
+
+    /// Encode a 0RGB framebuffer (as handed to `presentWithWriter`) to PNG
+    #[napi]
+    pub fn encode_png(view: Uint32Array, width: u32, height: u32) -> Result<Buffer> {
+        encode_png_raw(view.as_ref(), None, width, height)
+            .map(Buffer::from)
+            .map_err(|e| napi_reason!("failed to encode png: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blend, namespace::{BlitOptions, Image, ImageFilter}, with_alpha};
+
+    fn image(width: u32, height: u32, pixels: Vec<u32>) -> Image {
+        Image { width, height, pixels, alpha: None }
+    }
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(blend(with_alpha(0x00FF_0000, 0xFF), 0x0000_00FF), 0x00FF_0000);
+        assert_eq!(blend(with_alpha(0x00FF_0000, 0), 0x0000_00FF), 0x0000_00FF);
+        assert_eq!(blend(with_alpha(0x00FF_0000, 0x80), 0x0000_0000), 0x0080_0000);
+    }
+
+    #[test]
+    fn test_blit_nearest_upscale() {
+        let src = image(2, 1, vec![0x0011_1111, 0x0022_2222]);
+        let mut dest = vec![0; 4 * 2];
+        let options = BlitOptions { width: Some(4), height: Some(2), ..Default::default() };
+        src.blit(&mut dest, 4, 2, &options);
+        assert_eq!(dest, vec![
+            0x0011_1111, 0x0011_1111, 0x0022_2222, 0x0022_2222,
+            0x0011_1111, 0x0011_1111, 0x0022_2222, 0x0022_2222,
+        ]);
+    }
+
+    #[test]
+    fn test_blit_clipped() {
+        let src = image(2, 2, vec![1, 2, 3, 4]);
+        let mut dest = vec![0; 2 * 2];
+        let options = BlitOptions { x: Some(-1), y: Some(1), ..Default::default() };
+        src.blit(&mut dest, 2, 2, &options);
+        assert_eq!(dest, vec![0, 0, 2, 0]);
+    }
+
+    #[test]
+    fn test_blit_bilinear_identity() {
+        let src = image(2, 2, vec![0x0000_0010, 0x0000_0020, 0x0000_0030, 0x0000_0040]);
+        let mut dest = vec![0; 4];
+        let options = BlitOptions { filter: Some(ImageFilter::Bilinear), ..Default::default() };
+        src.blit(&mut dest, 2, 2, &options);
+        assert_eq!(dest, src.pixels);
+    }
+}
@@ -1,47 +1,202 @@
-use std::alloc::Layout;
+use std::{
+    alloc::{alloc_zeroed, dealloc, Layout},
+    ptr::NonNull,
+    sync::Arc,
+};
 
-struct Trunk<const N: usize>;
+/// Buffers are cache line aligned so rows can be processed with wide loads
+const CHUNK_ALIGN: usize = 64;
 
-impl<const N: usize> Trunk<N> {
-    const LAYOUT: Layout = Layout::new::<[u8; N]>();
+pub(crate) struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
 }
 
+unsafe impl Send for Chunk {}
+unsafe impl Sync for Chunk {}
+
+impl Chunk {
+    pub(crate) fn new(size: usize) -> Option<Self> {
+        let layout = Layout::from_size_align(size, CHUNK_ALIGN).ok()?;
+        let ptr = NonNull::new(unsafe { alloc_zeroed(layout) })?;
+        Some(Self { ptr, layout })
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.layout.size()
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+#[napi(js_name = "Extra")]
 pub mod namespace {
-    use std::alloc::Layout;
+    use super::*;
+    use crate::napi_reason;
     use napi::bindgen_prelude::*;
 
-    struct DynamicBuffer {
-        pub(crate) count: f64,
-        pub(crate) per: f64,
-        pub(crate) ratio: Option<f64>,
+    /// Pool of fixed-size pixel buffers, handed out as `Uint32Array` views over `SharedArrayBuffer`s,
+    /// so a worker can fill them.
+    ///
+    /// A view stays valid after `release`, but its memory may be handed to the next `acquire`,
+    /// so it must not be written to anymore.
+    #[napi]
+    pub struct DynamicBuffer {
+        pub(crate) per: usize,
+        /// Pixels per row, known when the pool was made with `forFrame`
+        pub(crate) width: Option<u32>,
+        pub(crate) ratio: f64,
+        pub(crate) chunks: Vec<SharedChunk>,
+        pub(crate) in_use: Vec<bool>,
+        env: Env,
     }
 
+    /// The view handed out by `acquire`, the pool holds it so the buffer is reused
+    pub(crate) struct SharedChunk {
+        view: ObjectRef,
+        ptr: *const u8,
+    }
+
+    #[napi]
     impl DynamicBuffer {
-        pub fn new(count: f64, per: f64, ratio: Option<f64>) -> Result<Self> {
+        /// `count` buffers of `per` bytes each, growing by `ratio` (default 1.5) when exhausted
+        #[napi(constructor)]
+        pub fn new(env: Env, count: f64, per: f64, ratio: Option<f64>) -> Result<Self> {
             if count != count.floor() || count <= 0. {
-                return napi_reason!("The count is not an int or lower than 0");
+                return Err(napi_reason!("The count is not an int or lower than 0"));
             }
             if per != per.floor() || per <= 0. {
-                return napi_reason!("The per is not an int or lower than 0");
+                return Err(napi_reason!("The per is not an int or lower than 0"));
+            }
+            if per % 4. != 0. {
+                return Err(napi_reason!("The per must be a multiple of 4 to hold u32 pixels"));
             }
             let ratio = ratio.unwrap_or(1.5);
-            
+
             if ratio <= 1. {
-                return napi_reason!("The ratio must be greater than 1");
+                return Err(napi_reason!("The ratio must be greater than 1"));
             }
 
-            let init_size = count * per * ratio;
+            let mut pool = Self {
+                per: per as usize,
+                width: None,
+                ratio,
+                chunks: Vec::new(),
+                in_use: Vec::new(),
+                env,
+            };
+            pool.grow_to(count as usize)?;
+            Ok(pool)
+        }
+
+        /// Pool sized for `width * height` pixels per buffer, `BufferSurface.presentPooled` reads them row by row
+        #[napi(factory)]
+        pub fn for_frame(env: Env, width: u32, height: u32, count: f64, ratio: Option<f64>) -> Result<Self> {
+            let mut pool = Self::new(env, count, width as f64 * height as f64 * 4., ratio)?;
+            pool.width = Some(width);
+            Ok(pool)
+        }
+
+        /// Bytes per buffer
+        #[napi(getter)]
+        pub fn per(&self) -> f64 {
+            self.per as f64
+        }
+
+        #[napi(getter)]
+        pub fn capacity(&self) -> u32 {
+            self.chunks.len() as u32
+        }
 
-            if init_size % per != 0. {
-                return napi_reason!("The init_size is not a multiple of per");
+        #[napi(getter)]
+        pub fn available(&self) -> u32 {
+            self.in_use.iter().filter(|used| !**used).count() as u32
+        }
+
+        /// Take a free buffer, growing the pool if every buffer is in use
+        #[napi]
+        pub fn acquire(&mut self) -> Result<Uint32Array> {
+            let index = match self.in_use.iter().position(|used| !*used) {
+                Some(index) => index,
+                None => {
+                    let index = self.chunks.len();
+                    let grown = ((index as f64) * self.ratio).ceil() as usize;
+                    self.grow_to(grown.max(index + 1))?;
+                    index
+                }
+            };
+
+            self.in_use[index] = true;
+            let view = self.chunks[index].view.get_value(&self.env)?;
+            unsafe { Uint32Array::from_napi_value(self.env.raw(), view.raw()) }
+        }
+
+        /// Give a buffer obtained from `acquire` back to the pool
+        #[napi]
+        pub fn release(&mut self, view: Uint32Array) -> Result<()> {
+            let index = self.index_of(&view)
+                .ok_or_else(|| napi_reason!("the view does not belong to this pool"))?;
+
+            if !self.in_use[index] {
+                return Err(napi_reason!("the buffer has already been released"));
             }
+            self.in_use[index] = false;
+            Ok(())
+        }
 
-            let count = count as usize;
-            let per = per as usize;
-            
-            let size = per * ratio;
-            let layout = Layout::from_size_align(1, per).unwrap();
-            todo!();
+        /// Drop free buffers above `count`, buffers in use are kept
+        #[napi]
+        pub fn shrink_to(&mut self, count: u32) {
+            let mut index = self.chunks.len();
+            while index > count as usize {
+                index -= 1;
+                if !self.in_use[index] {
+                    let _ = self.chunks.remove(index).view.unref(&self.env);
+                    self.in_use.remove(index);
+                }
+            }
         }
     }
-}
\ No newline at end of file
+
+    impl DynamicBuffer {
+        fn grow_to(&mut self, count: usize) -> Result<()> {
+            let global = self.env.get_global()?;
+            let shared_array_buffer = global.get_named_property_unchecked::<Function<u32, Unknown>>("SharedArrayBuffer")?;
+            let uint32_array = global.get_named_property_unchecked::<Function<Unknown, Unknown>>("Uint32Array")?;
+
+            while self.chunks.len() < count {
+                let buffer = shared_array_buffer.new_instance(self.per as u32)
+                    .map_err(|e| napi_reason!("failed to allocate {} bytes: {e}", self.per))?;
+                let view = uint32_array.new_instance(buffer)?;
+                let ptr = unsafe { Uint32Array::from_napi_value(self.env.raw(), view.raw())? }.as_ptr().cast();
+                let view = unsafe { view.cast::<Object>()? }.create_ref()?;
+                self.chunks.push(SharedChunk { view, ptr });
+                self.in_use.push(false);
+            }
+            Ok(())
+        }
+
+        pub(crate) fn index_of(&self, view: &Uint32Array) -> Option<usize> {
+            let ptr = view.as_ref().as_ptr().cast::<u8>();
+            self.chunks.iter().position(|chunk| std::ptr::eq(chunk.ptr, ptr))
+        }
+    }
+
+    impl Drop for DynamicBuffer {
+        fn drop(&mut self) {
+            for chunk in self.chunks.drain(..) {
+                let _ = chunk.view.unref(&self.env);
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
 pub mod time;
 pub mod surface;
+pub mod image;
 mod interval;
 mod sleep;
 mod executor;
-mod raw_window;
-// mod memory;
\ No newline at end of file
+pub(crate) mod raw_window;
+pub mod memory;
+pub mod frame_queue;
+pub mod popup;
+pub mod clipboard;
\ No newline at end of file
@@ -0,0 +1,217 @@
+use namespace::{PopupAlign, PopupOptions, PopupRect, PopupSide};
+
+impl PopupSide {
+    fn opposite(self) -> Self {
+        match self {
+            PopupSide::Bottom => PopupSide::Top,
+            PopupSide::Top => PopupSide::Bottom,
+            PopupSide::Right => PopupSide::Left,
+            PopupSide::Left => PopupSide::Right,
+        }
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, PopupSide::Bottom | PopupSide::Top)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Rect {
+    x: i64,
+    y: i64,
+    width: i64,
+    height: i64,
+}
+
+impl From<&PopupRect> for Rect {
+    fn from(rect: &PopupRect) -> Self {
+        Self { x: rect.x as i64, y: rect.y as i64, width: rect.width as i64, height: rect.height as i64 }
+    }
+}
+
+impl Rect {
+    fn right(&self) -> i64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y + self.height
+    }
+}
+
+fn aligned(start: i64, anchor_len: i64, len: i64, align: PopupAlign) -> i64 {
+    match align {
+        PopupAlign::Start => start,
+        PopupAlign::Center => start + (anchor_len - len) / 2,
+        PopupAlign::End => start + anchor_len - len,
+    }
+}
+
+fn place_on(anchor: Rect, width: i64, height: i64, side: PopupSide, align: PopupAlign, gap: i64) -> (i64, i64) {
+    match side {
+        PopupSide::Bottom => (aligned(anchor.x, anchor.width, width, align), anchor.bottom() + gap),
+        PopupSide::Top => (aligned(anchor.x, anchor.width, width, align), anchor.y - gap - height),
+        PopupSide::Right => (anchor.right() + gap, aligned(anchor.y, anchor.height, height, align)),
+        PopupSide::Left => (anchor.x - gap - width, aligned(anchor.y, anchor.height, height, align)),
+    }
+}
+
+/// Space left between the anchor and the bounds edge on `side`
+fn room(anchor: Rect, bounds: Rect, side: PopupSide, gap: i64) -> i64 {
+    match side {
+        PopupSide::Bottom => bounds.bottom() - anchor.bottom() - gap,
+        PopupSide::Top => anchor.y - bounds.y - gap,
+        PopupSide::Right => bounds.right() - anchor.right() - gap,
+        PopupSide::Left => anchor.x - bounds.x - gap,
+    }
+}
+
+fn clamp_into(value: i64, len: i64, start: i64, bounds_len: i64) -> i64 {
+    value.min(start + bounds_len - len).max(start)
+}
+
+/// Returns the popup origin, the side it ended up on, and whether that side was flipped
+pub(crate) fn place(anchor: &PopupRect, width: u32, height: u32, bounds: Option<&PopupRect>, options: &PopupOptions) -> (i64, i64, PopupSide, bool) {
+    let anchor = Rect::from(anchor);
+    let (width, height) = (width as i64, height as i64);
+    let side = options.side.unwrap_or(PopupSide::Bottom);
+    let align = options.align.unwrap_or(PopupAlign::Start);
+    let gap = options.gap.unwrap_or(0) as i64;
+
+    let Some(bounds) = bounds.map(Rect::from) else {
+        let (x, y) = place_on(anchor, width, height, side, align, gap);
+        return (x, y, side, false);
+    };
+
+    let needed = |side: PopupSide| if side.is_vertical() { height } else { width };
+    let flipped = options.flip.unwrap_or(true)
+        && room(anchor, bounds, side, gap) < needed(side)
+        && room(anchor, bounds, side.opposite(), gap) > room(anchor, bounds, side, gap);
+    let side = if flipped { side.opposite() } else { side };
+
+    let (mut x, mut y) = place_on(anchor, width, height, side, align, gap);
+    if options.slide.unwrap_or(true) {
+        x = clamp_into(x, width, bounds.x, bounds.width);
+        y = clamp_into(y, height, bounds.y, bounds.height);
+    }
+
+    (x, y, side, flipped)
+}
+
+#[napi(js_name = "Extra")]
+pub mod namespace {
+    use super::place;
+    use napi::bindgen_prelude::*;
+    use winit::dpi::PhysicalPosition;
+    use crate::{dpi::Position, napi_reason, window::Window};
+
+    /// Rectangle in physical pixels
+    #[napi(object)]
+    #[derive(Clone)]
+    pub struct PopupRect {
+        pub x: i32,
+        pub y: i32,
+        pub width: u32,
+        pub height: u32,
+    }
+
+    #[napi(string_enum)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub enum PopupSide {
+        Bottom,
+        Top,
+        Right,
+        Left,
+    }
+
+    #[napi(string_enum)]
+    #[derive(Clone, Copy)]
+    pub enum PopupAlign {
+        Start,
+        Center,
+        End,
+    }
+
+    #[napi(object)]
+    #[derive(Clone, Default)]
+    pub struct PopupOptions {
+        /// Preferred side of the anchor, default `Bottom`
+        pub side: Option<PopupSide>,
+        /// Alignment along the anchor edge, default `Start`
+        pub align: Option<PopupAlign>,
+        /// Distance between the anchor and the popup
+        pub gap: Option<i32>,
+        /// Move to the opposite side when the preferred one has not enough room, default true
+        pub flip: Option<bool>,
+        /// Shift the popup back inside the bounds, default true
+        pub slide: Option<bool>,
+    }
+
+    #[napi(object)]
+    pub struct PopupPlacement {
+        /// Outer position for `WindowAttributes.withPosition`
+        pub position: Position,
+        pub side: PopupSide,
+        pub flipped: bool,
+    }
+
+    /// Place a `width`x`height` popup next to `anchor`, keeping it inside `bounds` when given
+    #[napi]
+    pub fn place_popup(anchor: PopupRect, width: u32, height: u32, bounds: Option<PopupRect>, options: Option<PopupOptions>) -> PopupPlacement {
+        let (x, y, side, flipped) = place(&anchor, width, height, bounds.as_ref(), &options.unwrap_or_default());
+        PopupPlacement { position: Position::from(PhysicalPosition::new(x as i32, y as i32)), side, flipped }
+    }
+
+    /// Same as `placePopup` with `anchor` relative to the parent's client area, kept on the parent's monitor.
+    ///
+    /// Wayland does not let clients position windows, so the result is only useful on other platforms.
+    #[napi]
+    pub fn popup_position(parent: &Window, anchor: PopupRect, width: u32, height: u32, options: Option<PopupOptions>) -> Result<PopupPlacement> {
+        let parent = parent.inner()?;
+        let origin = parent.inner_position().map_err(|e| napi_reason!("{e}"))?;
+        let anchor = PopupRect { x: anchor.x + origin.x, y: anchor.y + origin.y, ..anchor };
+
+        let bounds = parent.current_monitor().map(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            PopupRect { x: position.x, y: position.y, width: size.width, height: size.height }
+        });
+
+        Ok(place_popup(anchor, width, height, bounds, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{place, namespace::{PopupAlign, PopupOptions, PopupRect, PopupSide}};
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> PopupRect {
+        PopupRect { x, y, width, height }
+    }
+
+    #[test]
+    fn test_place_below() {
+        let (x, y, side, flipped) = place(&rect(10, 10, 50, 20), 100, 80, Some(&rect(0, 0, 800, 600)), &PopupOptions::default());
+        assert_eq!((x, y, side, flipped), (10, 30, PopupSide::Bottom, false));
+    }
+
+    #[test]
+    fn test_flip_at_bottom_edge() {
+        let (x, y, side, flipped) = place(&rect(10, 560, 50, 20), 100, 80, Some(&rect(0, 0, 800, 600)), &PopupOptions::default());
+        assert_eq!((x, y, side, flipped), (10, 480, PopupSide::Top, true));
+    }
+
+    #[test]
+    fn test_slide_at_right_edge() {
+        let options = PopupOptions { align: Some(PopupAlign::Center), gap: Some(4), ..Default::default() };
+        let (x, y, _, _) = place(&rect(780, 10, 20, 20), 100, 80, Some(&rect(0, 0, 800, 600)), &options);
+        assert_eq!((x, y), (700, 34));
+    }
+
+    #[test]
+    fn test_no_flip_without_room() {
+        let options = PopupOptions { side: Some(PopupSide::Right), slide: Some(false), ..Default::default() };
+        let (x, _, side, flipped) = place(&rect(0, 0, 20, 20), 100, 80, Some(&rect(0, 0, 60, 600)), &options);
+        assert_eq!((x, side, flipped), (20, PopupSide::Right, false));
+    }
+}
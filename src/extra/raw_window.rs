@@ -16,6 +16,59 @@ pub mod namespace {
         pub window_handle: BigInt,
         pub display_handle: BigInt,
     }
+
+    /// Window created outside napi-winit, `handle` is the HWND, NSView, X11 window id or `wl_surface` pointer
+    #[napi(object)]
+    pub struct RawWindowHandle {
+        pub system: SurfaceSystem,
+        pub handle: BigInt,
+    }
+}
+
+pub(crate) mod rwh_06_convert {
+    use std::{ffi::c_void, num::NonZeroIsize, ptr::NonNull};
+    use napi::bindgen_prelude::*;
+    use rwh_06::{
+        AppKitWindowHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle, WaylandWindowHandle,
+        Win32WindowHandle, XlibWindowHandle,
+    };
+    use super::namespace::{RawWindowHandle as JsRawWindowHandle, SurfaceSystem};
+    use crate::napi_reason;
+
+    pub(crate) fn from_window(window: &winit::window::Window) -> Result<RawWindowHandle> {
+        window.window_handle()
+            .map(|handle| handle.as_raw())
+            .map_err(|e| napi_reason!("{e}"))
+    }
+
+    pub(crate) fn from_js(raw: &JsRawWindowHandle) -> Result<RawWindowHandle> {
+        let (_, handle, _) = raw.handle.get_u64();
+        let pointer = || NonNull::new(handle as usize as *mut c_void)
+            .ok_or_else(|| napi_reason!("window handle must not be null"));
+
+        let raw = match raw.system {
+            SurfaceSystem::Win32 => {
+                let hwnd = NonZeroIsize::new(handle as isize)
+                    .ok_or_else(|| napi_reason!("window handle must not be null"))?;
+                RawWindowHandle::Win32(Win32WindowHandle::new(hwnd))
+            }
+            SurfaceSystem::Cocoa => RawWindowHandle::AppKit(AppKitWindowHandle::new(pointer()?)),
+            SurfaceSystem::X11 => RawWindowHandle::Xlib(XlibWindowHandle::new(handle as _)),
+            SurfaceSystem::Wayland => RawWindowHandle::Wayland(WaylandWindowHandle::new(pointer()?)),
+        };
+        Ok(raw)
+    }
+
+    /// winit panics when handed a window handle of another backend
+    pub(crate) fn matches_display(window: &RawWindowHandle, display: &RawDisplayHandle) -> bool {
+        matches!(
+            (window, display),
+            (RawWindowHandle::Win32(_), RawDisplayHandle::Windows(_))
+                | (RawWindowHandle::AppKit(_), RawDisplayHandle::AppKit(_))
+                | (RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_), RawDisplayHandle::Xlib(_) | RawDisplayHandle::Xcb(_))
+                | (RawWindowHandle::Wayland(_), RawDisplayHandle::Wayland(_))
+        )
+    }
 }
 
 #[napi(js_name = "Extra")]
@@ -27,8 +80,8 @@ pub mod rwh_05_impl {
 
     #[napi]
     pub fn get_rwh_05_options(window: &Window) -> Result<SurfaceOptions> {
-        let raw_window_handle = window.inner.raw_window_handle();
-        let raw_display_handle = window.inner.raw_display_handle();
+        let raw_window_handle = window.inner()?.raw_window_handle();
+        let raw_display_handle = window.inner()?.raw_display_handle();
 
         match (raw_window_handle, raw_display_handle) {
             #[cfg(target_os = "windows")]
@@ -84,12 +137,12 @@ mod rwh_06_impl {
 
     // #[napi]
     pub fn get_rwh_06_options(window: &Window) -> Result<SurfaceOptions> {
-        let window_handle = match window.inner.window_handle() {
+        let window_handle = match window.inner()?.window_handle() {
             Err(e) => return Err(napi_reason!("{e}")),
             Ok(handle) => handle.as_raw(),
         };
 
-        let display_handle = match window.inner.display_handle() {
+        let display_handle = match window.inner()?.display_handle() {
             Err(e) => return Err(napi_reason!("{e}")),
             Ok(handle) => handle.as_raw(),
         };
@@ -6,10 +6,30 @@ fn flat_result<T, E>(result: Result<T, E>) -> (Option<E>, Option<T>) {
     }
 }
 
+/// Copy a `src_width` wide frame into a `width` wide buffer, cropped or padded with black
+fn copy_rows(src: &[u32], src_width: usize, dest: &mut [u32], width: usize, height: usize) {
+    let src_height = src.len().checked_div(src_width).unwrap_or(0);
+    if (width, height) == (src_width, src_height) {
+        dest.copy_from_slice(src);
+        return;
+    }
+
+    dest.fill(0);
+    let (copy_width, copy_height) = (width.min(src_width), height.min(src_height));
+    for row in 0..copy_height {
+        let (dest_start, src_start) = (row * width, row * src_width);
+        dest[dest_start..dest_start + copy_width].copy_from_slice(&src[src_start..src_start + copy_width]);
+    }
+}
+
 #[napi(js_name = "Extra")]
 pub mod namespace {
     use super::*;
     use crate::{
+        extra::image::namespace::{BlitOptions, Image},
+        extra::image::encode_png_raw,
+        extra::memory::namespace::DynamicBuffer,
+        extra::frame_queue::namespace::FrameQueue,
         window::Window,
         napi_reason,
         ok_or_reason,
@@ -25,30 +45,35 @@ pub mod namespace {
         num::{NonZero, NonZeroU32},
         ptr::NonNull,
         slice,
+        sync::Arc,
     };
     use std::ops::DerefMut;
 
     #[napi]
-    struct BufferSurface<'scope> {
-        pub(crate) window: &'scope winit::window::Window,
-        pub(crate) context: Option<Context<&'scope winit::window::Window>>,
-        pub(crate) surface: Option<Surface<&'scope winit::window::Window, &'scope winit::window::Window>>,
+    struct BufferSurface {
+        pub(crate) window: Arc<winit::window::Window>,
+        pub(crate) context: Option<Context<Arc<winit::window::Window>>>,
+        pub(crate) surface: Option<Surface<Arc<winit::window::Window>, Arc<winit::window::Window>>>,
+        /// Copy of the last presented frame for `capturePng`, softbuffer can't read back on every platform
+        pub(crate) last_frame: Option<(NonZeroU32, NonZeroU32, Vec<u32>)>,
     }
 
     #[napi]
-    impl<'scope> BufferSurface<'scope> {
+    impl BufferSurface {
+        /// The surface shares the native window, a closed window stays alive until the surface is dropped
         #[napi(constructor)]
-        pub fn new(window: &'scope mut Window) -> Self {
-            Self {
-                window: &mut window.inner,
+        pub fn new(window: &Window) -> Result<Self> {
+            Ok(Self {
+                window: window.shared()?,
                 context: None,
                 surface: None,
-            }
+                last_frame: None,
+            })
         }
     }
 
     #[napi]
-    impl BufferSurface<'_> {
+    impl BufferSurface {
         #[napi]
         pub fn present_with_typed(&mut self, input: Uint32Array) -> Result<()> {
             let src_len = input.len();
@@ -83,6 +108,7 @@ pub mod namespace {
             })
         }
 
+        /// Blocks until the callback has run, see `FrameQueue` for rendering from a worker
         #[napi]
         pub fn present_with_threadsafe_writer<'scope>(
             &mut self, env: Env,
@@ -102,29 +128,100 @@ pub mod namespace {
                 Ok(())
             })
         }
+
+        /// Present a buffer acquired from `pool` and release it back to the pool.
+        ///
+        /// Pools made with `forFrame` are read row by row and cropped or padded to the surface, other pools
+        /// have to match the surface size. The buffer stays acquired when presenting fails
+        #[napi]
+        pub fn present_pooled(&mut self, pool: &mut DynamicBuffer, view: Uint32Array) -> Result<()> {
+            if pool.index_of(&view).is_none() {
+                return Err(napi_reason!("the view does not belong to the pool"));
+            }
+
+            let src_len = view.len();
+            self.present(|width, height, buffer| {
+                let dest_len = buffer.len();
+
+                match pool.width {
+                    Some(src_width) => copy_rows(view.as_ref(), src_width as usize, buffer, width.get() as usize, height.get() as usize),
+                    None if src_len == dest_len => buffer.copy_from_slice(view.as_ref()),
+                    None => return Err(napi_reason!("pooled buffer ({src_len}) does not match the surface ({dest_len}), make the pool with `forFrame` to crop it")),
+                }
+                Ok(())
+            })?;
+
+            pool.release(view)
+        }
+
+        /// Present the newest frame submitted to `queue`, or re-present the current one.
+        ///
+        /// Returns whether a newly submitted frame was shown, call it on `RedrawRequested`
+        #[napi]
+        pub fn present_from_queue(&mut self, queue: &FrameQueue) -> Result<bool> {
+            let Some((frame, fresh)) = queue.shared.take_frame()
+            else { return Ok(false) };
+
+            let pixels = frame.pixels();
+
+            self.present(|width, height, buffer| {
+                copy_rows(pixels, frame.width as usize, buffer, width.get() as usize, height.get() as usize);
+                Ok(())
+            })?;
+
+            Ok(fresh)
+        }
+
+        /// Clear the surface and draw the image, stretched to the whole surface unless a rect is given
+        #[napi]
+        pub fn present_with_image(&mut self, image: &Image, options: Option<BlitOptions>) -> Result<()> {
+            self.present(|width, height, buffer| {
+                let options = options.clone().unwrap_or_else(|| BlitOptions {
+                    width: Some(width.get()),
+                    height: Some(height.get()),
+                    ..Default::default()
+                });
+                buffer.fill(0);
+                image.blit(buffer.deref_mut(), width.get(), height.get(), &options);
+                Ok(())
+            })
+        }
+
+        /// Encode the last presented frame to PNG, also on Wayland where the surface can't be read back
+        #[napi]
+        pub fn capture_png(&self) -> Result<Buffer> {
+            let Some((width, height, pixels)) = &self.last_frame
+            else { return Err(napi_reason!("nothing has been presented yet")) };
+
+            encode_png_raw(pixels, None, width.get(), height.get())
+                .map(Buffer::from)
+                .map_err(|e| napi_reason!("failed to encode png: {e}"))
+        }
     }
 
-    impl BufferSurface<'_> {
-        pub(crate) fn present<F>(&mut self, mut write_fn: F) -> Result<()>
-        where
-            F: FnMut(NonZero<u32>, NonZero<u32>, &mut softbuffer::Buffer<&winit::window::Window, &winit::window::Window>) -> Result<()>,
-        {
+    impl BufferSurface {
+        pub(crate) fn surface(&mut self) -> Result<&mut Surface<Arc<winit::window::Window>, Arc<winit::window::Window>>> {
             let context = match self.context {
                 Some(ref mut context) => context,
-                None => match Context::new(self.window) {
+                None => match Context::new(self.window.clone()) {
                     Ok(context) => self.context.insert(context),
                     Err(e) => return Err(napi_reason!("Failed to create buffer context: {e}")),
                 }
             };
 
-            let surface = match self.surface {
-                Some(ref mut surface) => surface,
-                None => match Surface::new(&context, self.window) {
-                    Ok(surface) => self.surface.insert(surface),
-                    Err(e) => return Err(napi_reason!("Failed to create buffer surface: {e}")),
+            match self.surface {
+                Some(ref mut surface) => Ok(surface),
+                None => match Surface::new(&context, self.window.clone()) {
+                    Ok(surface) => Ok(self.surface.insert(surface)),
+                    Err(e) => Err(napi_reason!("Failed to create buffer surface: {e}")),
                 }
-            };
+            }
+        }
 
+        pub(crate) fn present<F>(&mut self, mut write_fn: F) -> Result<()>
+        where
+            F: FnMut(NonZero<u32>, NonZero<u32>, &mut softbuffer::Buffer<Arc<winit::window::Window>, Arc<winit::window::Window>>) -> Result<()>,
+        {
             let size = self.window.inner_size();
 
             let Some(width) = NonZeroU32::new(size.width)
@@ -133,6 +230,10 @@ pub mod namespace {
             let Some(height) = NonZeroU32::new(size.height)
             else { return Err(napi_reason!("invalid window size [height: {}]", size.height)) };
 
+            // reuse the allocation, the surface borrows all of self until the frame is presented
+            let mut frame = self.last_frame.take().map(|(_, _, frame)| frame).unwrap_or_default();
+            let surface = self.surface()?;
+
             if let Err(e) = surface.resize(width, height) {
                 return Err(napi_reason!("failed to resize surface: {e}"));
             }
@@ -147,8 +248,34 @@ pub mod namespace {
 
             ok_or_reason!(write_fn(width, height, &mut buffer); "{}");
 
+            frame.clear();
+            frame.extend_from_slice(&buffer);
+
             buffer.present()
-                .map_err(|e| napi_reason!("failed to access buffer: {e}"))
+                .map_err(|e| napi_reason!("failed to access buffer: {e}"))?;
+
+            self.last_frame = Some((width, height, frame));
+            Ok(())
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::copy_rows;
+
+    #[test]
+    fn test_copy_rows_crops_wider_frame() {
+        let src = [1, 2, 3, 4, 5, 6];
+        let mut dest = [9; 4];
+        copy_rows(&src, 3, &mut dest, 2, 2);
+        assert_eq!(dest, [1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_copy_rows_pads_smaller_frame() {
+        let src = [1, 2];
+        let mut dest = [9; 6];
+        copy_rows(&src, 2, &mut dest, 3, 2);
+        assert_eq!(dest, [1, 2, 0, 0, 0, 0]);
+    }
+}
@@ -113,11 +113,37 @@ pub enum NamedKey {
     F21, F22, F23, F24, F25, F26, F27, F28, F29, F30, F31, F32, F33, F34, F35
 }
 
-#[proxy_enum(origin_type = winit::keyboard::KeyLocation, string_enum, skip_backward)]
+#[proxy_enum(origin_type = winit::keyboard::KeyLocation, string_enum, skip_backward, serde)]
 pub enum KeyLocation { Standard, Left, Right, Numpad }
 
-#[proxy_flags(origin = winit::keyboard::ModifiersState, flags = (SHIFT, CONTROL, ALT, SUPER))]
+#[proxy_flags(origin = winit::keyboard::ModifiersState, flags = (SHIFT, CONTROL, ALT, SUPER), serde)]
 pub struct ModifiersState;
 
 #[proxy_enum(origin_type = winit::keyboard::ModifiersKeyState, string_enum, skip_backward)]
-pub enum ModifiersKeyState { Pressed, Unknown }
\ No newline at end of file
+pub enum ModifiersKeyState { Pressed, Unknown }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{KeyLocation, ModifiersState};
+    use crate::event::MouseButton;
+
+    #[test]
+    fn test_serde_round_trip() {
+        let location = serde_json::to_value(KeyLocation::Numpad).unwrap();
+        assert_eq!(location, json!("Numpad"));
+        assert!(matches!(serde_json::from_value(location).unwrap(), KeyLocation::Numpad));
+
+        let button = serde_json::to_value(MouseButton::Other { field_0: 7 }).unwrap();
+        let MouseButton::Other { field_0: value } = serde_json::from_value(button).unwrap() else { panic!("expected Other") };
+        assert_eq!(value, 7);
+
+        let modifiers = ModifiersState { flag_shift: true, flag_control: false, flag_alt: true, flag_super: false };
+        let names = serde_json::to_value(&modifiers).unwrap();
+        assert_eq!(names, json!(["SHIFT", "ALT"]));
+        let parsed: ModifiersState = serde_json::from_value(json!(["alt", "SHIFT"])).unwrap();
+        assert!(parsed.equals(&modifiers));
+        assert!(serde_json::from_value::<ModifiersState>(json!(["HYPER"])).is_err());
+    }
+}
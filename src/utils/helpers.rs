@@ -1,6 +1,13 @@
+/// Invalid UTF-8 is replaced with U+FFFD, use `path_to_bytes` for the exact path
 #[inline]
 pub(crate) fn path_buf_to_string(path_buf: std::path::PathBuf) -> String {
-    path_buf.to_str().unwrap().into()
+    path_buf.to_string_lossy().into_owned()
+}
+
+/// Raw bytes on unix, WTF-8 on windows
+#[inline]
+pub(crate) fn path_to_bytes(path: &std::path::Path) -> Vec<u8> {
+    path.as_os_str().as_encoded_bytes().to_vec()
 }
 
 #[inline]
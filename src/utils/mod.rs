@@ -1,3 +1,4 @@
 pub mod helpers;
 pub mod alias;
-pub mod wrap;
\ No newline at end of file
+pub mod wrap;
+pub(crate) mod scope;
\ No newline at end of file
@@ -0,0 +1,115 @@
+use std::cell::Cell;
+
+use crate::napi_reason;
+
+// bumped whenever a handler call returns, objects borrowed from winit are only valid for one call
+thread_local! {
+    static GENERATION: Cell<u64> = const { Cell::new(0) };
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+    static DEFERRED: Cell<bool> = const { Cell::new(false) };
+    // bumped when `runApp` consumed the event loop
+    static LOOPS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Open while winit runs one of the application handlers
+pub(crate) struct HandlerScope {
+    deferred: bool,
+}
+
+impl HandlerScope {
+    pub(crate) fn enter() -> Self {
+        DEPTH.set(DEPTH.get() + 1);
+        Self { deferred: DEFERRED.replace(false) }
+    }
+
+    /// For handlers that are queued and run after the call returned, what they get stays valid as long
+    /// as the event loop
+    pub(crate) fn enter_deferred() -> Self {
+        DEPTH.set(DEPTH.get() + 1);
+        Self { deferred: DEFERRED.replace(true) }
+    }
+}
+
+impl Drop for HandlerScope {
+    fn drop(&mut self) {
+        DEPTH.set(DEPTH.get() - 1);
+        DEFERRED.set(self.deferred);
+        GENERATION.set(GENERATION.get().wrapping_add(1));
+    }
+}
+
+/// The event loop is gone, objects handed out to deferred handlers expire
+pub(crate) fn loop_consumed() {
+    LOOPS.set(LOOPS.get().wrapping_add(1));
+}
+
+/// Generation a borrowed object was handed out in
+#[derive(Clone, Copy)]
+pub(crate) enum Stamp {
+    Call(u64),
+    Loop(u64),
+}
+
+impl Stamp {
+    pub(crate) fn now() -> Self {
+        if DEFERRED.get() {
+            return Self::Loop(LOOPS.get());
+        }
+        Self::Call(GENERATION.get())
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            Self::Call(generation) => DEPTH.get() > 0 && GENERATION.get() == generation,
+            Self::Loop(generation) => LOOPS.get() == generation,
+        }
+    }
+
+    pub(crate) fn check(&self, name: &str) -> napi::Result<()> {
+        match self {
+            _ if self.is_valid() => Ok(()),
+            Self::Call(_) => Err(napi_reason!("{name} is only valid during the handler call it was passed to, it can't be kept or used after an await")),
+            Self::Loop(_) => Err(napi_reason!("{name} can't be used after `runApp` returned")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HandlerScope, Stamp};
+
+    #[test]
+    fn test_stamp_expires_with_scope() {
+        let outside = Stamp::now();
+        assert!(!outside.is_valid());
+
+        let scope = HandlerScope::enter();
+        let inside = Stamp::now();
+        assert!(inside.is_valid());
+        drop(scope);
+        assert!(!inside.is_valid());
+
+        let _next = HandlerScope::enter();
+        assert!(!inside.is_valid());
+        assert!(Stamp::now().is_valid());
+    }
+
+    #[test]
+    fn test_deferred_stamp_outlives_scope() {
+        let scope = HandlerScope::enter_deferred();
+        let deferred = Stamp::now();
+        drop(scope);
+        assert!(deferred.is_valid());
+
+        // nested handlers of other runners are not deferred
+        let outer = HandlerScope::enter_deferred();
+        let inner = HandlerScope::enter();
+        assert!(matches!(Stamp::now(), Stamp::Call(_)));
+        drop(inner);
+        assert!(matches!(Stamp::now(), Stamp::Loop(_)));
+        drop(outer);
+
+        super::loop_consumed();
+        assert!(!deferred.is_valid());
+    }
+}
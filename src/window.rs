@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use napi::bindgen_prelude::*;
 
 use winit::window::{
@@ -6,14 +8,74 @@ use winit::window::{
 
 use proc::{proxy_enum, proxy_flags, proxy_impl, proxy_wrap};
 
+pub(crate) mod registry;
+pub(crate) mod platform;
+pub(crate) mod state;
+pub(crate) mod object;
+pub(crate) mod logical_size;
+pub(crate) mod activation;
+pub(crate) mod drag_drop;
+pub(crate) mod ime;
+
 use crate::{
     utils::helpers::{option_map, option_into, pipe, ref_clone_into, result_map, result_into, result_err_reason, vec_map, vec_map_into},
     cursor::{Cursor, CursorIcon},
     dpi::{Position, Size},
     monitor::MonitorHandle,
+    extra::raw_window::{namespace::RawWindowHandle, rwh_06_convert},
+    window::{
+        object::{Assigned, Field, WindowAttributesObject},
+        platform::PlatformWindowAttributes,
+    },
     napi_reason,
 };
 
+/// Bookkeeping every runner does before a window event is handed to js
+pub(crate) fn before_window_event(window_id: winit::window::WindowId, event: &mut winit::event::WindowEvent) {
+    logical_size::before_window_event(window_id, event);
+    activation::before_window_event(event);
+    drag_drop::before_window_event(window_id, event);
+    ime::before_window_event(window_id, event);
+}
+
+/// Native parent of a window, only read while the window is created
+#[derive(Clone)]
+pub(crate) enum ParentWindow {
+    /// Attached to the parent, see `WindowAttributes.withParentWindow`
+    Child(rwh_06::RawWindowHandle),
+    /// Top-level window kept above its owner, see `WindowAttributes.withOwnerWindow`
+    Owner(rwh_06::RawWindowHandle),
+}
+
+impl ParentWindow {
+    pub(crate) fn handle(&self) -> &rwh_06::RawWindowHandle {
+        match self {
+            ParentWindow::Child(handle) | ParentWindow::Owner(handle) => handle,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn with_owner_window(attrs: winit::window::WindowAttributes, owner: rwh_06::RawWindowHandle) -> winit::window::WindowAttributes {
+    use winit::platform::windows::WindowAttributesExtWindows;
+    match owner {
+        rwh_06::RawWindowHandle::Win32(handle) => attrs.with_owner_window(handle.hwnd.get()),
+        _ => attrs,
+    }
+}
+
+// child windows of an NSWindow already follow it around without being clipped
+#[cfg(target_os = "macos")]
+fn with_owner_window(attrs: winit::window::WindowAttributes, owner: rwh_06::RawWindowHandle) -> winit::window::WindowAttributes {
+    unsafe { attrs.with_parent_window(Some(owner)) }
+}
+
+// `withOwnerWindow` throws on other platforms, winit has no owner relation there
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn with_owner_window(attrs: winit::window::WindowAttributes, _owner: rwh_06::RawWindowHandle) -> winit::window::WindowAttributes {
+    attrs
+}
+
 #[napi]
 #[derive(Clone)]
 pub struct WindowAttributes {
@@ -36,12 +98,12 @@ pub struct WindowAttributes {
     pub(crate) window_level: WindowLevel,
     pub(crate) active: bool,
     pub(crate) cursor: Cursor,
-    // #[cfg(feature = "rwh_06")]
-    // pub(crate) parent_window: Option<SendSyncRawWindowHandle>,
+    pub(crate) parent_window: Option<ParentWindow>,
     pub(crate) fullscreen: Option<Fullscreen>,
-    // Platform-specific configuration.
-    // #[allow(dead_code)]
-    // pub(crate) platform_specific: PlatformSpecificWindowAttributes,
+    // Platform-specific configuration, applied by `PendingAttributes.build` once the backend is known
+    pub(crate) platform_specific: PlatformWindowAttributes,
+    pub(crate) activation_token: Option<ActivationToken>,
+    pub(crate) assigned: Assigned,
 }
 
 impl Default for WindowAttributes {
@@ -68,15 +130,36 @@ impl Default for WindowAttributes {
             content_protected: false,
             cursor: Cursor::default(),
             active: true,
-            // #[cfg(feature = "rwh_06")]
-            // parent_window: None,
-            // platform_specific: Default::default(),
+            parent_window: None,
+            platform_specific: Default::default(),
+            activation_token: None,
+            assigned: Assigned::default(),
         }
     }
 }
 
-impl Into<winit::window::WindowAttributes> for WindowAttributes {
-    fn into(self) -> winit::window::WindowAttributes {
+/// `WindowAttributes` converted for winit, the platform attributes wait for the display the window opens on
+pub(crate) struct PendingAttributes {
+    attrs: winit::window::WindowAttributes,
+    platform_specific: PlatformWindowAttributes,
+}
+
+impl PendingAttributes {
+    /// The only way to winit's attributes, so no path that creates a window skips the platform attributes
+    pub(crate) fn build(self, display: &rwh_06::RawDisplayHandle) -> winit::window::WindowAttributes {
+        platform::apply(self.attrs, self.platform_specific, display)
+    }
+}
+
+impl From<WindowAttributes> for PendingAttributes {
+    fn from(value: WindowAttributes) -> Self {
+        let platform_specific = value.platform_specific.clone();
+        Self { attrs: value.into_winit(), platform_specific }
+    }
+}
+
+impl WindowAttributes {
+    fn into_winit(self) -> winit::window::WindowAttributes {
         let attrs = winit::window::WindowAttributes::default()
             .with_resizable(self.resizable)
             .with_enabled_buttons(self.enabled_buttons.into())
@@ -130,10 +213,33 @@ impl Into<winit::window::WindowAttributes> for WindowAttributes {
             None => attrs,
         };
 
+        // the handle was taken from a live window or handed in by the user, who keeps the parent alive
+        let attrs = match self.parent_window {
+            Some(ParentWindow::Child(handle)) => unsafe { attrs.with_parent_window(Some(handle)) },
+            Some(ParentWindow::Owner(handle)) => with_owner_window(attrs, handle),
+            None => attrs,
+        };
+
+        let attrs = match self.activation_token {
+            Some(token) => with_activation_token(attrs, token.0),
+            None => attrs,
+        };
+
         attrs
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+fn with_activation_token(attrs: winit::window::WindowAttributes, token: winit::window::ActivationToken) -> winit::window::WindowAttributes {
+    use winit::platform::startup_notify::WindowAttributesExtStartupNotify;
+    attrs.with_activation_token(token)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+fn with_activation_token(attrs: winit::window::WindowAttributes, _token: winit::window::ActivationToken) -> winit::window::WindowAttributes {
+    attrs
+}
+
 #[napi]
 impl WindowAttributes {
     #[napi(constructor)]
@@ -144,66 +250,127 @@ impl WindowAttributes {
     #[napi(ts_return_type = "this")]
     pub fn with_inner_size(&mut self, size: Size) -> &Self {
         self.inner_size = Some(size);
+        self.assigned.insert(Field::InnerSize);
         self
     }
 
+    #[napi(getter)]
+    pub fn inner_size(&self) -> Option<Size> {
+        self.inner_size.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_min_inner_size(&mut self, min_size: Size) -> &Self {
         self.min_inner_size = Some(min_size);
+        self.assigned.insert(Field::MinInnerSize);
         self
     }
 
+    #[napi(getter)]
+    pub fn min_inner_size(&self) -> Option<Size> {
+        self.min_inner_size.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_max_inner_size(&mut self, max_size: Size) -> &Self {
         self.max_inner_size = Some(max_size);
+        self.assigned.insert(Field::MaxInnerSize);
         self
     }
 
+    #[napi(getter)]
+    pub fn max_inner_size(&self) -> Option<Size> {
+        self.max_inner_size.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_position(&mut self, position: Position) -> &Self {
         self.position = Some(position);
+        self.assigned.insert(Field::Position);
         self
     }
 
+    #[napi(getter)]
+    pub fn position(&self) -> Option<Position> {
+        self.position.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_resizable(&mut self, resizable: bool) -> &Self {
         self.resizable = resizable;
+        self.assigned.insert(Field::Resizable);
         self
     }
 
+    #[napi(getter)]
+    pub fn resizable(&self) -> bool {
+        self.resizable
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_enabled_buttons(&mut self, buttons: &WindowButtons) -> &Self {
         self.enabled_buttons = buttons.clone();
+        self.assigned.insert(Field::EnabledButtons);
         self
     }
 
+    #[napi(getter)]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.enabled_buttons.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_title(&mut self, title: String) -> &Self {
         self.title = title;
+        self.assigned.insert(Field::Title);
         self
     }
 
+    #[napi(getter)]
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> &Self {
         self.fullscreen = fullscreen;
+        self.assigned.insert(Field::Fullscreen);
         self
     }
 
+    #[napi(getter)]
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.fullscreen.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_maximized(&mut self, maximized: bool) -> &Self {
         self.maximized = maximized;
+        self.assigned.insert(Field::Maximized);
         self
     }
 
+    #[napi(getter)]
+    pub fn maximized(&self) -> bool {
+        self.maximized
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_visible(&mut self, visible: bool) -> &Self {
         self.visible = visible;
+        self.assigned.insert(Field::Visible);
         self
     }
 
+    #[napi(getter)]
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_transparent(&mut self, transparent: bool) -> &Self {
         self.transparent = transparent;
+        self.assigned.insert(Field::Transparent);
         self
     }
 
@@ -215,71 +382,200 @@ impl WindowAttributes {
     #[napi(ts_return_type = "this")]
     pub fn with_blur(&mut self, blur: bool) -> &Self {
         self.blur = blur;
+        self.assigned.insert(Field::Blur);
         self
     }
 
+    #[napi(getter)]
+    pub fn blur(&self) -> bool {
+        self.blur
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_decorations(&mut self, decorations: bool) -> &Self {
         self.decorations = decorations;
+        self.assigned.insert(Field::Decorations);
         self
     }
 
+    #[napi(getter)]
+    pub fn decorations(&self) -> bool {
+        self.decorations
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_window_level(&mut self, level: WindowLevel) -> &Self {
         self.window_level = level;
+        self.assigned.insert(Field::WindowLevel);
         self
     }
 
-    // #[inline]
-    pub fn with_window_icon(&mut self, window_icon: Option<Icon>) -> &Self {
-        self.window_icon = window_icon;
+    #[napi(getter)]
+    pub fn window_level(&self) -> WindowLevel {
+        self.window_level.clone()
+    }
+
+    #[napi(ts_return_type = "this")]
+    pub fn with_window_icon(&mut self, window_icon: Option<&Icon>) -> &Self {
+        self.window_icon = window_icon.cloned();
+        self.assigned.insert(Field::WindowIcon);
         self
     }
 
+    #[napi(getter)]
+    pub fn window_icon(&self) -> Option<Icon> {
+        self.window_icon.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_theme(&mut self, theme: Option<Theme>) -> &Self {
         self.preferred_theme = theme;
+        self.assigned.insert(Field::Theme);
         self
     }
 
+    #[napi(getter)]
+    pub fn theme(&self) -> Option<Theme> {
+        self.preferred_theme.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_resize_increments(&mut self, resize_increments: Size) -> &Self {
         self.resize_increments = Some(resize_increments.into());
+        self.assigned.insert(Field::ResizeIncrements);
         self
     }
 
+    #[napi(getter)]
+    pub fn resize_increments(&self) -> Option<Size> {
+        self.resize_increments.clone()
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_content_protected(&mut self, protected: bool) -> &Self {
         self.content_protected = protected;
+        self.assigned.insert(Field::ContentProtected);
         self
     }
 
+    #[napi(getter)]
+    pub fn content_protected(&self) -> bool {
+        self.content_protected
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_active(&mut self, active: bool) -> &Self {
         self.active = active;
+        self.assigned.insert(Field::Active);
         self
     }
 
+    #[napi(getter)]
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
     #[napi(ts_return_type = "this")]
     pub fn with_cursor(&mut self, cursor: &Cursor) -> &Self {
         self.cursor = cursor.clone();
+        self.assigned.insert(Field::Cursor);
         self
     }
 
-    // #[cfg(feature = "rwh_06")]
-    // #[inline]
-    // pub unsafe fn with_parent_window(
-    //     mut self,
-    //     parent_window: Option<rwh_06::RawWindowHandle>,
-    // ) -> Self {
-    //     self.parent_window = parent_window.map(SendSyncRawWindowHandle);
-    //     self
-    // }
-
-    // #[cfg(feature = "rwh_06")]
-    // pub fn parent_window(&self) -> Option<&rwh_06::RawWindowHandle> {
-    //     self.parent_window.as_ref().map(|handle| &handle.0)
-    // }
+    #[napi(getter)]
+    pub fn cursor(&self) -> Cursor {
+        self.cursor.clone()
+    }
+
+    /// Child window that moves with `parent`, the parent must stay open until the window is created.
+    ///
+    /// Windows and X11 confine the child to the parent's client area, use `withOwnerWindow` for popups on Windows.
+    /// Unsupported on Wayland.
+    #[napi(ts_return_type = "this")]
+    pub fn with_parent_window(&mut self, parent: &Window) -> Result<&Self> {
+        self.parent_window = Some(ParentWindow::Child(rwh_06_convert::from_window(parent.inner()?)?));
+        self.assigned.insert(Field::ParentWindow);
+        Ok(self)
+    }
+
+    /// Same as `withParentWindow` for a window created outside napi-winit
+    #[napi(ts_return_type = "this")]
+    pub fn with_raw_parent_window(&mut self, parent: RawWindowHandle) -> Result<&Self> {
+        self.parent_window = Some(ParentWindow::Child(rwh_06_convert::from_js(&parent)?));
+        self.assigned.insert(Field::ParentWindow);
+        Ok(self)
+    }
+
+    /// Top-level window that stays above `owner` and minimizes with it, for tool palettes and popup menus.
+    ///
+    /// Windows and macOS only, throws elsewhere: winit can't set the X11 transient-for hint or a Wayland parent.
+    #[napi(ts_return_type = "this")]
+    pub fn with_owner_window(&mut self, owner: &Window) -> Result<&Self> {
+        if cfg!(not(any(target_os = "windows", target_os = "macos"))) {
+            return Err(napi_reason!("owner windows are only supported on Windows and macOS"));
+        }
+        self.parent_window = Some(ParentWindow::Owner(rwh_06_convert::from_window(owner.inner()?)?));
+        self.assigned.insert(Field::ParentWindow);
+        Ok(self)
+    }
+
+    #[napi(ts_return_type = "this")]
+    pub fn without_parent_window(&mut self) -> &Self {
+        self.parent_window = None;
+        self.assigned.insert(Field::ParentWindow);
+        self
+    }
+
+    #[napi(getter)]
+    pub fn has_parent_window(&self) -> bool {
+        self.parent_window.is_some()
+    }
+
+    /// X11 and Wayland specific attributes, replacing the ones set before
+    #[napi(ts_return_type = "this")]
+    pub fn with_platform(&mut self, platform: PlatformWindowAttributes) -> &Self {
+        self.platform_specific = platform;
+        self.assigned.insert(Field::Platform);
+        self
+    }
+
+    #[napi(getter)]
+    pub fn platform(&self) -> PlatformWindowAttributes {
+        self.platform_specific.clone()
+    }
+
+    /// Lets the window take focus on X11 and Wayland when it is opened on behalf of another app
+    #[napi(ts_return_type = "this")]
+    pub fn with_activation_token(&mut self, token: &ActivationToken) -> &Self {
+        self.activation_token = Some(token.clone());
+        self.assigned.insert(Field::ActivationToken);
+        self
+    }
+
+    #[napi(getter)]
+    pub fn activation_token(&self) -> Option<ActivationToken> {
+        self.activation_token.clone()
+    }
+
+    #[napi(factory)]
+    pub fn from_object(object: WindowAttributesObject) -> Result<Self> {
+        let mut attributes = Self::default();
+        attributes.assign(object)?;
+        Ok(attributes)
+    }
+
+    /// Every field, including the defaults, so two snapshots can be diffed
+    #[napi]
+    pub fn to_object(&self) -> WindowAttributesObject {
+        self.to_plain()
+    }
+
+    /// Take over the fields that were set on `other`, a field set to its default value resets it here
+    #[napi(ts_return_type = "this")]
+    pub fn merge(&mut self, other: &WindowAttributes) -> &Self {
+        self.merge_assigned(other);
+        self
+    }
 }
 
 #[napi]
@@ -308,15 +604,15 @@ impl From<OriginFullscreen> for Fullscreen {
 }
 
 /**[winit::window::WindowButtons]*/
-#[proxy_flags(origin = winit::window::WindowButtons, flags = (CLOSE, MINIMIZE, MAXIMIZE))]
+#[proxy_flags(origin = winit::window::WindowButtons, flags = (CLOSE, MINIMIZE, MAXIMIZE), serde)]
 #[derive(Clone)]
 pub struct WindowButtons;
 
-#[proxy_enum(origin_type = winit::window::WindowLevel, string_enum)]
+#[proxy_enum(origin_type = winit::window::WindowLevel, string_enum, serde)]
 #[derive(Clone)]
 pub enum WindowLevel { AlwaysOnBottom, Normal, AlwaysOnTop }
 
-#[proxy_enum(origin_type = winit::window::Theme, string_enum)]
+#[proxy_enum(origin_type = winit::window::Theme, string_enum, serde)]
 #[derive(Clone)]
 pub enum Theme { Light, Dark }
 
@@ -356,11 +652,60 @@ impl WindowId {
 
 /**[winit::window::ActivationToken]*/
 #[proxy_wrap(origin_type = winit::window::ActivationToken)]
+#[derive(Clone)]
 pub struct ActivationToken;
 
+#[napi]
+impl ActivationToken {
+    /// Wrap a token received over IPC, e.g. from a second instance of the app
+    #[napi(factory)]
+    pub fn from_raw(token: String) -> Self {
+        Self(winit::window::ActivationToken::from_raw(token))
+    }
+
+    #[napi(getter)]
+    pub fn token(&self) -> String {
+        self.0.clone().into_raw()
+    }
+
+    /// Unset `XDG_ACTIVATION_TOKEN` and `DESKTOP_STARTUP_ID` so child processes don't reuse them
+    #[napi]
+    pub fn reset_env() {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+        winit::platform::startup_notify::reset_activation_token_env();
+    }
+}
+
 /**[winit::window::Window]*/
-#[proxy_wrap(origin_type = winit::window::Window, field_name = inner)]
-pub struct Window;
+#[napi]
+pub struct Window {
+    pub(crate) inner: Option<Arc<winit::window::Window>>,
+}
+
+impl From<winit::window::Window> for Window {
+    fn from(value: winit::window::Window) -> Self {
+        Self { inner: Some(Arc::new(value)) }
+    }
+}
+
+impl Window {
+    /// `closeWindow` releases the native window, later uses throw
+    pub(crate) fn inner(&self) -> Result<&winit::window::Window> {
+        self.inner.as_deref().ok_or_else(closed)
+    }
+
+    pub(crate) fn shared(&self) -> Result<Arc<winit::window::Window>> {
+        self.inner.clone().ok_or_else(closed)
+    }
+
+    pub(crate) fn release(&mut self) -> Option<Arc<winit::window::Window>> {
+        self.inner.take()
+    }
+}
+
+fn closed() -> Error {
+    napi_reason!("the window was closed")
+}
 
 #[napi]
 impl Window {
@@ -368,9 +713,31 @@ impl Window {
     pub fn default_attributes() -> WindowAttributes {
         WindowAttributes::default()
     }
+
+    /// Keep the logical inner size when the window moves to a monitor with another scale factor,
+    /// instead of the size the platform suggests. `ScaleFactorChanged` handlers can still override it.
+    #[napi]
+    pub fn set_keep_logical_size(&self, keep: bool) -> Result<()> {
+        logical_size::set(self.inner()?, keep);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn keeps_logical_size(&self) -> Result<bool> {
+        Ok(logical_size::is_kept(self.inner()?.id()))
+    }
+
+    /// Resolves once the matching `ActivationTokenDone` event is dispatched, X11 and Wayland only
+    #[napi]
+    pub async fn request_activation_token(&self) -> Result<ActivationToken> {
+        let receiver = activation::request(self.inner()?).map_err(|e| napi_reason!("{e}"))?;
+        receiver.await
+            .map(ActivationToken::from)
+            .map_err(|_| napi_reason!("the activation token request was dropped"))
+    }
 }
 
-#[proxy_impl(access_expr = self.inner)]
+#[proxy_impl(access_expr = self.inner()?)]
 impl Window {
     fn id(&self) -> WindowId;
     fn scale_factor(&self) -> f64;
@@ -386,7 +753,7 @@ impl Window {
     fn set_min_inner_size(&self, #[proxy_impl(skip_conv_arg)] min_size: Option<Size>);
 */
 
-#[proxy_impl(access_expr = self.inner)]
+#[proxy_impl(access_expr = self.inner()?)]
 impl Window {
     #[proxy_impl(conv_return = [ result_map(Into::into), result_err_reason ])]
     fn inner_position(&self) -> Result<Position>;
@@ -413,7 +780,7 @@ impl Window {
     fn set_resize_increments(&self, #[proxy_impl(skip_conv_arg)] increments: Option<Size>);
 }
 
-#[proxy_impl(access_expr = self.inner)]
+#[proxy_impl(access_expr = self.inner()?)]
 impl Window {
     fn set_title(&self, #[proxy_impl(conv_arg = title.as_str())] title: String);
 
@@ -474,7 +841,7 @@ impl Window {
     fn title(&self) -> String;
 }
 
-#[proxy_impl(access_expr = self.inner)]
+#[proxy_impl(access_expr = self.inner()?)]
 impl Window {
     fn set_cursor(&self, #[proxy_impl(conv_arg = Clone::clone)] cursor: &Cursor);
 
@@ -500,7 +867,7 @@ impl Window {
     fn set_cursor_hittest(&self, hittest: bool) -> Result<()>;
 }
 
-#[proxy_impl(access_expr = self.inner)]
+#[proxy_impl(access_expr = self.inner()?)]
 impl Window {
     #[proxy_impl(conv_return = option_into)]
     fn current_monitor(&self) -> Option<MonitorHandle>;
@@ -0,0 +1,35 @@
+use std::sync::Mutex;
+
+use tokio::sync::oneshot;
+use winit::{event::WindowEvent, event_loop::AsyncRequestSerial};
+
+/// Requests waiting for their `ActivationTokenDone`, the token may be delivered to a runtime thread
+static PENDING: Mutex<Vec<(AsyncRequestSerial, oneshot::Sender<winit::window::ActivationToken>)>> = Mutex::new(Vec::new());
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+pub(crate) fn request(window: &winit::window::Window) -> Result<oneshot::Receiver<winit::window::ActivationToken>, String> {
+    use winit::platform::startup_notify::WindowExtStartupNotify;
+
+    // hold the lock while requesting, so the answer can't be dispatched before it is registered
+    let mut pending = PENDING.lock().unwrap();
+    let serial = window.request_activation_token().map_err(|e| e.to_string())?;
+    let (sender, receiver) = oneshot::channel();
+    pending.push((serial, sender));
+    Ok(receiver)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+pub(crate) fn request(_window: &winit::window::Window) -> Result<oneshot::Receiver<winit::window::ActivationToken>, String> {
+    Err("activation tokens are only available on X11 and Wayland".to_owned())
+}
+
+pub(crate) fn before_window_event(event: &WindowEvent) {
+    let WindowEvent::ActivationTokenDone { serial, token } = event else { return };
+
+    let mut pending = PENDING.lock().unwrap();
+    if let Some(index) = pending.iter().position(|(pending_serial, _)| pending_serial == serial) {
+        let (_, sender) = pending.swap_remove(index);
+        // the promise may have been dropped already
+        let _ = sender.send(token.clone());
+    }
+}
@@ -0,0 +1,162 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf};
+
+use winit::{dpi::PhysicalPosition, event::WindowEvent, window::WindowId};
+
+use crate::{dpi::Position, event::DragDropEvent};
+
+#[derive(PartialEq, Debug)]
+enum Session {
+    Enter(Vec<PathBuf>, Option<PhysicalPosition<f64>>),
+    Over(PhysicalPosition<f64>),
+    Drop(Vec<PathBuf>, Option<PhysicalPosition<f64>>),
+    Leave,
+}
+
+impl From<Session> for DragDropEvent {
+    fn from(session: Session) -> Self {
+        let files = |files: Vec<PathBuf>| files.into_iter().map(Into::into).collect();
+        match session {
+            Session::Enter(paths, position) => DragDropEvent::DragEnter { files: files(paths), position: position.map(Position::from) },
+            Session::Over(position) => DragDropEvent::DragOver { position: Position::from(position) },
+            Session::Drop(paths, position) => DragDropEvent::Drop { files: files(paths), position: position.map(Position::from) },
+            Session::Leave => DragDropEvent::DragLeave,
+        }
+    }
+}
+
+/// winit reports every file on its own, the files of one drag all arrive before the next `about_to_wait`
+#[derive(Default)]
+struct Tracker {
+    cursor: Option<PhysicalPosition<f64>>,
+    hovered: Vec<PathBuf>,
+    dropped: Vec<PathBuf>,
+    entered: bool,
+}
+
+impl Tracker {
+    fn on_event(&mut self, event: &WindowEvent, queue: &mut Vec<Session>) {
+        match event {
+            WindowEvent::HoveredFile(path) if !self.entered => self.hovered.push(path.clone()),
+            WindowEvent::DroppedFile(path) => self.dropped.push(path.clone()),
+            WindowEvent::HoveredFileCancelled => {
+                if self.entered {
+                    queue.push(Session::Leave);
+                }
+                self.hovered.clear();
+                self.entered = false;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some(*position);
+                if self.entered {
+                    queue.push(Session::Over(*position));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn flush(&mut self, queue: &mut Vec<Session>) {
+        if !self.entered && !self.hovered.is_empty() {
+            queue.push(Session::Enter(std::mem::take(&mut self.hovered), self.cursor));
+            self.entered = true;
+        }
+        if self.dropped.is_empty() {
+            return;
+        }
+        // platforms that skip the hover still get a whole session
+        if !self.entered {
+            queue.push(Session::Enter(self.dropped.clone(), self.cursor));
+        }
+        queue.push(Session::Drop(std::mem::take(&mut self.dropped), self.cursor));
+        self.hovered.clear();
+        self.entered = false;
+    }
+}
+
+#[derive(Default)]
+struct DragDrop {
+    trackers: HashMap<WindowId, Tracker>,
+    queue: Vec<(WindowId, Session)>,
+}
+
+thread_local! {
+    static DRAG_DROP: RefCell<DragDrop> = RefCell::new(DragDrop::default());
+}
+
+pub(crate) fn before_window_event(id: WindowId, event: &WindowEvent) {
+    DRAG_DROP.with_borrow_mut(|DragDrop { trackers, queue }| {
+        if let WindowEvent::Destroyed = event {
+            trackers.remove(&id);
+            return;
+        }
+        let mut sessions = Vec::new();
+        trackers.entry(id).or_default().on_event(event, &mut sessions);
+        queue.extend(sessions.into_iter().map(|session| (id, session)));
+    });
+}
+
+/// Events of every window since the last call, called from `about_to_wait`
+pub(crate) fn take_events() -> Vec<(WindowId, DragDropEvent)> {
+    DRAG_DROP.with_borrow_mut(|DragDrop { trackers, queue }| {
+        for (id, tracker) in trackers.iter_mut() {
+            let mut sessions = Vec::new();
+            tracker.flush(&mut sessions);
+            queue.extend(sessions.into_iter().map(|session| (*id, session)));
+        }
+        queue.drain(..).map(|(id, session)| (id, session.into())).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use winit::{dpi::PhysicalPosition, event::{DeviceId, WindowEvent}};
+    use super::{Session, Tracker};
+
+    fn run(tracker: &mut Tracker, events: Vec<WindowEvent>) -> Vec<Session> {
+        let mut queue = Vec::new();
+        for event in &events {
+            tracker.on_event(event, &mut queue);
+        }
+        tracker.flush(&mut queue);
+        queue
+    }
+
+    fn moved(x: f64, y: f64) -> WindowEvent {
+        WindowEvent::CursorMoved { device_id: DeviceId::dummy(), position: PhysicalPosition::new(x, y) }
+    }
+
+    #[test]
+    fn test_files_grouped() {
+        let mut tracker = Tracker::default();
+        let (a, b) = (PathBuf::from("/a"), PathBuf::from("/b"));
+
+        let queue = run(&mut tracker, vec![moved(1., 2.), WindowEvent::HoveredFile(a.clone()), WindowEvent::HoveredFile(b.clone())]);
+        assert_eq!(queue, vec![Session::Enter(vec![a.clone(), b.clone()], Some(PhysicalPosition::new(1., 2.)))]);
+
+        let queue = run(&mut tracker, vec![moved(3., 4.)]);
+        assert_eq!(queue, vec![Session::Over(PhysicalPosition::new(3., 4.))]);
+
+        let queue = run(&mut tracker, vec![WindowEvent::DroppedFile(a.clone()), WindowEvent::DroppedFile(b.clone())]);
+        assert_eq!(queue, vec![Session::Drop(vec![a, b], Some(PhysicalPosition::new(3., 4.)))]);
+        assert!(run(&mut tracker, vec![moved(5., 6.)]).is_empty());
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let mut tracker = Tracker::default();
+        run(&mut tracker, vec![WindowEvent::HoveredFile(PathBuf::from("/a"))]);
+        assert_eq!(run(&mut tracker, vec![WindowEvent::HoveredFileCancelled]), vec![Session::Leave]);
+
+        // cancelled before the enter was reported, nothing to leave
+        assert!(run(&mut tracker, vec![WindowEvent::HoveredFile(PathBuf::from("/a")), WindowEvent::HoveredFileCancelled]).is_empty());
+    }
+
+    #[test]
+    fn test_drop_without_hover() {
+        let mut tracker = Tracker::default();
+        let a = PathBuf::from("/a");
+        let queue = run(&mut tracker, vec![WindowEvent::DroppedFile(a.clone())]);
+        assert_eq!(queue, vec![Session::Enter(vec![a.clone()], None), Session::Drop(vec![a], None)]);
+    }
+}
@@ -0,0 +1,211 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use napi::bindgen_prelude::*;
+use winit::{
+    dpi::{Position as OriginPosition, Size as OriginSize},
+    event::{Ime, WindowEvent},
+    window::WindowId as OriginWindowId,
+};
+
+use crate::{
+    dpi::{Position, Size},
+    napi_reason,
+    window::{ImePurpose, Window},
+};
+
+/// UTF-16 offset of the byte offset `byte`, `None` when it is not on a char boundary
+pub(crate) fn utf16_offset(text: &str, byte: usize) -> Option<u32> {
+    text.is_char_boundary(byte).then(|| text[..byte].encode_utf16().count() as u32)
+}
+
+struct Session {
+    /// Weak, a session must not keep its window alive or `Destroyed` would never end it
+    window: WeakReference<Window>,
+    enabled: bool,
+    preedit: String,
+    /// UTF-16 range, `None` hides the cursor
+    cursor: Option<(u32, u32)>,
+    commits: Vec<String>,
+    caret: Option<(OriginPosition, OriginSize)>,
+}
+
+impl Session {
+    fn place_candidates(&self) {
+        if let (Some((position, size)), Some(Ok(window))) = (self.caret, self.window.get().map(Window::inner)) {
+            window.set_ime_cursor_area(position, size);
+        }
+    }
+
+    fn window(&self) -> Result<&winit::window::Window> {
+        self.window.get().ok_or_else(|| napi_reason!("the window of this ImeSession was dropped"))?.inner()
+    }
+}
+
+thread_local! {
+    static SESSIONS: RefCell<HashMap<OriginWindowId, Session>> = RefCell::new(HashMap::new());
+}
+
+pub(crate) fn before_window_event(id: OriginWindowId, event: &WindowEvent) {
+    SESSIONS.with_borrow_mut(|sessions| {
+        if let WindowEvent::Destroyed = event {
+            sessions.remove(&id);
+            return;
+        }
+        let (Some(session), WindowEvent::Ime(ime)) = (sessions.get_mut(&id), event) else { return };
+
+        match ime {
+            Ime::Enabled => {
+                session.enabled = true;
+                session.place_candidates();
+            }
+            Ime::Preedit(text, cursor) => {
+                session.cursor = cursor.and_then(|(start, end)| Some((utf16_offset(text, start)?, utf16_offset(text, end)?)));
+                session.preedit = text.clone();
+                // some input methods forget the area once the composition changes
+                session.place_candidates();
+            }
+            Ime::Commit(text) => {
+                session.preedit.clear();
+                session.cursor = None;
+                session.commits.push(text.clone());
+            }
+            Ime::Disabled => {
+                session.enabled = false;
+                session.preedit.clear();
+                session.cursor = None;
+            }
+        }
+    });
+}
+
+/// Cursor inside the preedit text, in UTF-16 code units like js string indices
+#[napi(object)]
+pub struct ImeCursorRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Composition state of a window, kept up to date from its `Ime` events
+#[napi]
+pub struct ImeSession {
+    id: OriginWindowId,
+}
+
+impl ImeSession {
+    fn with<R>(&self, f: impl FnOnce(&mut Session) -> R) -> Result<R> {
+        self.try_with(|session| Ok(f(session)))
+    }
+
+    fn try_with<R>(&self, f: impl FnOnce(&mut Session) -> Result<R>) -> Result<R> {
+        SESSIONS.with_borrow_mut(|sessions| {
+            let session = sessions.get_mut(&self.id).ok_or_else(|| napi_reason!("ImeSession is closed"))?;
+            f(session)
+        })
+    }
+}
+
+#[napi]
+impl ImeSession {
+    /// Sessions of the same window share their state
+    #[napi(factory)]
+    pub fn create(window: Reference<Window>) -> Result<Self> {
+        let id = window.inner()?.id();
+        SESSIONS.with_borrow_mut(|sessions| {
+            sessions.entry(id).or_insert_with(|| Session {
+                window: window.downgrade(),
+                enabled: false,
+                preedit: String::new(),
+                cursor: None,
+                commits: Vec::new(),
+                caret: None,
+            });
+        });
+        Ok(Self { id })
+    }
+
+    /// Allow IME input, see `Window.setImeAllowed`
+    #[napi]
+    pub fn enable(&self, purpose: Option<ImePurpose>) -> Result<()> {
+        self.try_with(|session| {
+            let window = session.window()?;
+            window.set_ime_allowed(true);
+            if let Some(purpose) = purpose {
+                window.set_ime_purpose(purpose.into());
+            }
+            Ok(())
+        })
+    }
+
+    #[napi]
+    pub fn disable(&self) -> Result<()> {
+        self.try_with(|session| {
+            session.window()?.set_ime_allowed(false);
+            Ok(())
+        })
+    }
+
+    /// Whether the input method reported `Enabled`, which may come later than `enable()`
+    #[napi(getter)]
+    pub fn enabled(&self) -> Result<bool> {
+        self.with(|session| session.enabled)
+    }
+
+    #[napi(getter)]
+    pub fn composing(&self) -> Result<bool> {
+        self.with(|session| !session.preedit.is_empty())
+    }
+
+    #[napi(getter)]
+    pub fn preedit(&self) -> Result<String> {
+        self.with(|session| session.preedit.clone())
+    }
+
+    #[napi(getter)]
+    pub fn cursor(&self) -> Result<Option<ImeCursorRange>> {
+        self.with(|session| session.cursor.map(|(start, end)| ImeCursorRange { start, end }))
+    }
+
+    /// Committed text since the last call, oldest first
+    #[napi]
+    pub fn take_commits(&self) -> Result<Vec<String>> {
+        self.with(|session| std::mem::take(&mut session.commits))
+    }
+
+    /// Caret rectangle relative to the window, the candidate window follows it on every composition change
+    #[napi]
+    pub fn set_caret_area(&self, position: Position, size: Size) -> Result<()> {
+        self.with(|session| {
+            session.caret = Some((position.into(), size.into()));
+            session.place_candidates();
+        })
+    }
+
+    /// Stop tracking, every session of the window is closed
+    #[napi]
+    pub fn close(&self) {
+        SESSIONS.with_borrow_mut(|sessions| sessions.remove(&self.id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::utf16_offset;
+
+    #[test]
+    fn test_utf16_offset() {
+        let text = "a日本😀b";
+        assert_eq!(utf16_offset(text, 0), Some(0));
+        assert_eq!(utf16_offset(text, 1), Some(1));
+        assert_eq!(utf16_offset(text, 4), Some(2));
+        assert_eq!(utf16_offset(text, 7), Some(3));
+        // the emoji is a surrogate pair
+        assert_eq!(utf16_offset(text, 11), Some(5));
+        assert_eq!(utf16_offset(text, text.len()), Some(6));
+    }
+
+    #[test]
+    fn test_utf16_offset_not_on_boundary() {
+        assert_eq!(utf16_offset("日本", 2), None);
+        assert_eq!(utf16_offset("日本", 7), None);
+    }
+}
@@ -0,0 +1,59 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use winit::{dpi::PhysicalSize, event::WindowEvent, window::WindowId};
+
+/// Last known logical inner size, with the scale factor it was measured at
+#[derive(Clone, Copy)]
+struct Kept {
+    width: f64,
+    height: f64,
+    scale_factor: f64,
+}
+
+thread_local! {
+    static KEPT: RefCell<HashMap<WindowId, Kept>> = RefCell::new(HashMap::new());
+}
+
+pub(crate) fn set(window: &winit::window::Window, keep: bool) {
+    KEPT.with_borrow_mut(|kept| {
+        if !keep {
+            kept.remove(&window.id());
+            return;
+        }
+        let scale_factor = window.scale_factor();
+        let size = window.inner_size().to_logical::<f64>(scale_factor);
+        kept.insert(window.id(), Kept { width: size.width, height: size.height, scale_factor });
+    });
+}
+
+pub(crate) fn is_kept(id: WindowId) -> bool {
+    KEPT.with_borrow(|kept| kept.contains_key(&id))
+}
+
+/// Runs before the js handler, which can still request another size
+pub(crate) fn before_window_event(id: WindowId, event: &mut WindowEvent) {
+    KEPT.with_borrow_mut(|kept| {
+        let Some(entry) = kept.get_mut(&id) else { return };
+
+        match event {
+            WindowEvent::ScaleFactorChanged { scale_factor, inner_size_writer } => {
+                entry.scale_factor = *scale_factor;
+                let size = PhysicalSize::new(
+                    (entry.width * *scale_factor).round() as u32,
+                    (entry.height * *scale_factor).round() as u32,
+                );
+                // only fails once the writer expired, which can't happen before the handler returns
+                let _ = inner_size_writer.request_inner_size(size);
+            }
+            WindowEvent::Resized(size) => {
+                let size = size.to_logical::<f64>(entry.scale_factor);
+                entry.width = size.width;
+                entry.height = size.height;
+            }
+            WindowEvent::Destroyed => {
+                kept.remove(&id);
+            }
+            _ => {}
+        }
+    });
+}
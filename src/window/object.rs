@@ -0,0 +1,213 @@
+use napi::bindgen_prelude::*;
+
+use crate::{
+    cursor::{Cursor, CursorIcon},
+    dpi::{Position, Size},
+    window::{platform::PlatformWindowAttributes, ActivationToken, Fullscreen, Theme, WindowAttributes, WindowButtons, WindowLevel},
+};
+
+/// Fields of `WindowAttributes` that can be given a value
+#[derive(Clone, Copy)]
+pub(crate) enum Field {
+    InnerSize,
+    MinInnerSize,
+    MaxInnerSize,
+    Position,
+    Resizable,
+    EnabledButtons,
+    Title,
+    Maximized,
+    Visible,
+    Transparent,
+    Blur,
+    Decorations,
+    WindowIcon,
+    Theme,
+    ResizeIncrements,
+    ContentProtected,
+    WindowLevel,
+    Active,
+    Cursor,
+    ParentWindow,
+    Fullscreen,
+    Platform,
+    ActivationToken,
+}
+
+/// Fields that were set through a `with*` call or `fromObject`, even to their default value
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Assigned(u32);
+
+impl Assigned {
+    pub(crate) fn insert(&mut self, field: Field) {
+        self.0 |= 1 << field as u32;
+    }
+
+    pub(crate) fn contains(&self, field: Field) -> bool {
+        self.0 & (1 << field as u32) != 0
+    }
+}
+
+/// Plain data form of `WindowAttributes`, every field is optional and falls back to the default.
+///
+/// The icon and the parent window are native handles and are not part of it.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowAttributesObject {
+    pub inner_size: Option<Size>,
+    pub min_inner_size: Option<Size>,
+    pub max_inner_size: Option<Size>,
+    pub position: Option<Position>,
+    pub resizable: Option<bool>,
+    /// Names as in `WindowButtons.toNames`, matched ignoring case
+    #[napi(ts_type = "Array<'CLOSE' | 'MINIMIZE' | 'MAXIMIZE'>")]
+    pub enabled_buttons: Option<Vec<String>>,
+    pub title: Option<String>,
+    pub maximized: Option<bool>,
+    pub visible: Option<bool>,
+    pub transparent: Option<bool>,
+    pub blur: Option<bool>,
+    pub decorations: Option<bool>,
+    pub theme: Option<Theme>,
+    pub resize_increments: Option<Size>,
+    pub content_protected: Option<bool>,
+    pub window_level: Option<WindowLevel>,
+    pub active: Option<bool>,
+    /// Only cursor icons, custom cursors are left out of `toObject`
+    pub cursor: Option<CursorIcon>,
+    pub fullscreen: Option<Fullscreen>,
+    pub platform: Option<PlatformWindowAttributes>,
+    pub activation_token: Option<String>,
+}
+
+impl WindowAttributes {
+    pub(crate) fn assign(&mut self, object: WindowAttributesObject) -> Result<()> {
+        let WindowAttributesObject {
+            inner_size,
+            min_inner_size,
+            max_inner_size,
+            position,
+            resizable,
+            enabled_buttons,
+            title,
+            maximized,
+            visible,
+            transparent,
+            blur,
+            decorations,
+            theme,
+            resize_increments,
+            content_protected,
+            window_level,
+            active,
+            cursor,
+            fullscreen,
+            platform,
+            activation_token,
+        } = object;
+
+        macro_rules! assign {
+            ($($field: ident: $name: ident = $value: expr),* $(,)?) => {
+                $( if let Some(value) = $value {
+                    self.$field = value;
+                    self.assigned.insert(Field::$name);
+                } )*
+            };
+        }
+
+        assign! {
+            inner_size: InnerSize = inner_size.map(Some),
+            min_inner_size: MinInnerSize = min_inner_size.map(Some),
+            max_inner_size: MaxInnerSize = max_inner_size.map(Some),
+            position: Position = position.map(Some),
+            resizable: Resizable = resizable,
+            enabled_buttons: EnabledButtons = enabled_buttons.map(WindowButtons::from_names).transpose()?,
+            title: Title = title,
+            maximized: Maximized = maximized,
+            visible: Visible = visible,
+            transparent: Transparent = transparent,
+            blur: Blur = blur,
+            decorations: Decorations = decorations,
+            preferred_theme: Theme = theme.map(Some),
+            resize_increments: ResizeIncrements = resize_increments.map(Some),
+            content_protected: ContentProtected = content_protected,
+            window_level: WindowLevel = window_level,
+            active: Active = active,
+            cursor: Cursor = cursor.map(Cursor::from_icon),
+            fullscreen: Fullscreen = fullscreen.map(Some),
+            platform_specific: Platform = platform,
+            activation_token: ActivationToken = activation_token.map(|token| Some(ActivationToken::from_raw(token))),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_plain(&self) -> WindowAttributesObject {
+        let cursor = match &self.cursor.0 {
+            winit::window::Cursor::Icon(icon) => Some(CursorIcon::from(*icon)),
+            winit::window::Cursor::Custom(_) => None,
+        };
+
+        WindowAttributesObject {
+            inner_size: self.inner_size.clone(),
+            min_inner_size: self.min_inner_size.clone(),
+            max_inner_size: self.max_inner_size.clone(),
+            position: self.position.clone(),
+            resizable: Some(self.resizable),
+            enabled_buttons: Some(self.enabled_buttons.to_names()),
+            title: Some(self.title.clone()),
+            maximized: Some(self.maximized),
+            visible: Some(self.visible),
+            transparent: Some(self.transparent),
+            blur: Some(self.blur),
+            decorations: Some(self.decorations),
+            theme: self.preferred_theme.clone(),
+            resize_increments: self.resize_increments.clone(),
+            content_protected: Some(self.content_protected),
+            window_level: Some(self.window_level.clone()),
+            active: Some(self.active),
+            cursor,
+            fullscreen: self.fullscreen.clone(),
+            platform: Some(self.platform_specific.clone()),
+            activation_token: self.activation_token.as_ref().map(ActivationToken::token),
+        }
+    }
+
+    /// Takes over every field that was assigned on `other`
+    pub(crate) fn merge_assigned(&mut self, other: &WindowAttributes) {
+        macro_rules! merge {
+            ($($field: ident: $name: ident),* $(,)?) => {
+                $( if other.assigned.contains(Field::$name) {
+                    self.$field = other.$field.clone();
+                    self.assigned.insert(Field::$name);
+                } )*
+            };
+        }
+
+        merge! {
+            inner_size: InnerSize,
+            min_inner_size: MinInnerSize,
+            max_inner_size: MaxInnerSize,
+            position: Position,
+            resizable: Resizable,
+            enabled_buttons: EnabledButtons,
+            title: Title,
+            maximized: Maximized,
+            visible: Visible,
+            transparent: Transparent,
+            blur: Blur,
+            decorations: Decorations,
+            window_icon: WindowIcon,
+            preferred_theme: Theme,
+            resize_increments: ResizeIncrements,
+            content_protected: ContentProtected,
+            window_level: WindowLevel,
+            active: Active,
+            cursor: Cursor,
+            parent_window: ParentWindow,
+            fullscreen: Fullscreen,
+            platform_specific: Platform,
+            activation_token: ActivationToken,
+        }
+    }
+}
+
@@ -0,0 +1,189 @@
+use napi::bindgen_prelude::*;
+
+use crate::dpi::Size;
+
+/**[winit::platform::x11::WindowType]*/
+#[napi(string_enum)]
+#[derive(Clone, Copy)]
+pub enum X11WindowType {
+    Desktop,
+    Dock,
+    Toolbar,
+    Menu,
+    Utility,
+    Splash,
+    Dialog,
+    DropdownMenu,
+    PopupMenu,
+    Tooltip,
+    Notification,
+    Combo,
+    Dnd,
+    Normal,
+}
+
+/**[winit::platform::x11::WindowAttributesExtX11]*/
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct X11WindowAttributes {
+    /// Class part of WM_CLASS, should match the `StartupWMClass` of the `.desktop` file
+    pub class_name: Option<String>,
+    /// Instance part of WM_CLASS, defaults to `className`
+    pub instance_name: Option<String>,
+    /// `_NET_WM_WINDOW_TYPE` hints, defaults to `Normal`
+    pub window_types: Option<Vec<X11WindowType>>,
+    pub override_redirect: Option<bool>,
+    pub base_size: Option<Size>,
+    pub screen_id: Option<i32>,
+    pub visual_id: Option<u32>,
+    /// XEmbed into a foreign X window
+    pub embed_parent_window: Option<u32>,
+}
+
+/**[winit::platform::wayland::WindowAttributesExtWayland]*/
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WaylandWindowAttributes {
+    /// Should match the name of the `.desktop` file, without the extension
+    pub app_id: Option<String>,
+}
+
+/// Only the attributes of the backend the event loop runs on are applied
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct PlatformWindowAttributes {
+    pub x11: Option<X11WindowAttributes>,
+    pub wayland: Option<WaylandWindowAttributes>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+mod linux {
+    use std::cell::RefCell;
+    use rwh_06::RawDisplayHandle;
+    use winit::platform::{
+        wayland::WindowAttributesExtWayland,
+        x11::{WindowAttributesExtX11, WindowType},
+    };
+    use super::*;
+
+    impl From<X11WindowType> for WindowType {
+        fn from(value: X11WindowType) -> Self {
+            match value {
+                X11WindowType::Desktop => WindowType::Desktop,
+                X11WindowType::Dock => WindowType::Dock,
+                X11WindowType::Toolbar => WindowType::Toolbar,
+                X11WindowType::Menu => WindowType::Menu,
+                X11WindowType::Utility => WindowType::Utility,
+                X11WindowType::Splash => WindowType::Splash,
+                X11WindowType::Dialog => WindowType::Dialog,
+                X11WindowType::DropdownMenu => WindowType::DropdownMenu,
+                X11WindowType::PopupMenu => WindowType::PopupMenu,
+                X11WindowType::Tooltip => WindowType::Tooltip,
+                X11WindowType::Notification => WindowType::Notification,
+                X11WindowType::Combo => WindowType::Combo,
+                X11WindowType::Dnd => WindowType::Dnd,
+                X11WindowType::Normal => WindowType::Normal,
+            }
+        }
+    }
+
+    fn apply_x11(attrs: winit::window::WindowAttributes, x11: X11WindowAttributes) -> winit::window::WindowAttributes {
+        let X11WindowAttributes {
+            class_name,
+            instance_name,
+            window_types,
+            override_redirect,
+            base_size,
+            screen_id,
+            visual_id,
+            embed_parent_window,
+        } = x11;
+
+        let attrs = match (class_name, instance_name) {
+            (Some(class_name), Some(instance_name)) => WindowAttributesExtX11::with_name(attrs, class_name, instance_name),
+            (Some(class_name), None) => WindowAttributesExtX11::with_name(attrs, class_name.clone(), class_name),
+            (None, Some(instance_name)) => WindowAttributesExtX11::with_name(attrs, instance_name.clone(), instance_name),
+            (None, None) => attrs,
+        };
+
+        let attrs = match window_types {
+            Some(window_types) => attrs.with_x11_window_type(window_types.into_iter().map(Into::into).collect()),
+            None => attrs,
+        };
+
+        let attrs = match override_redirect {
+            Some(override_redirect) => attrs.with_override_redirect(override_redirect),
+            None => attrs,
+        };
+
+        let attrs = match base_size {
+            Some(base_size) => attrs.with_base_size(base_size),
+            None => attrs,
+        };
+
+        let attrs = match screen_id {
+            Some(screen_id) => attrs.with_x11_screen(screen_id),
+            None => attrs,
+        };
+
+        let attrs = match visual_id {
+            Some(visual_id) => attrs.with_x11_visual(visual_id),
+            None => attrs,
+        };
+
+        match embed_parent_window {
+            Some(parent_window_id) => attrs.with_embed_parent_window(parent_window_id),
+            None => attrs,
+        }
+    }
+
+    fn apply_wayland(attrs: winit::window::WindowAttributes, wayland: WaylandWindowAttributes) -> winit::window::WindowAttributes {
+        match wayland.app_id {
+            // the instance name is a no-op on wayland
+            Some(app_id) => WindowAttributesExtWayland::with_name(attrs, app_id, ""),
+            None => attrs,
+        }
+    }
+
+    thread_local! {
+        static DEFAULT_APP_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+    }
+
+    pub(crate) fn set_default_app_id(app_id: Option<String>) {
+        DEFAULT_APP_ID.set(app_id);
+    }
+
+    /// Names windows that don't name themselves after the app id given to `EventLoop.create`
+    fn with_default_app_id(mut platform: PlatformWindowAttributes) -> PlatformWindowAttributes {
+        let Some(app_id) = DEFAULT_APP_ID.with_borrow(Clone::clone) else { return platform };
+
+        let x11 = platform.x11.get_or_insert_with(Default::default);
+        if x11.class_name.is_none() && x11.instance_name.is_none() {
+            x11.class_name = Some(app_id.clone());
+        }
+        let wayland = platform.wayland.get_or_insert_with(Default::default);
+        if wayland.app_id.is_none() {
+            wayland.app_id = Some(app_id);
+        }
+        platform
+    }
+
+    pub(crate) fn apply(attrs: winit::window::WindowAttributes, platform: PlatformWindowAttributes, display: &RawDisplayHandle) -> winit::window::WindowAttributes {
+        match (display, with_default_app_id(platform)) {
+            (RawDisplayHandle::Xlib(_) | RawDisplayHandle::Xcb(_), PlatformWindowAttributes { x11: Some(x11), .. }) => apply_x11(attrs, x11),
+            (RawDisplayHandle::Wayland(_), PlatformWindowAttributes { wayland: Some(wayland), .. }) => apply_wayland(attrs, wayland),
+            _ => attrs,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+pub(crate) use linux::{apply, set_default_app_id};
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+pub(crate) fn set_default_app_id(_app_id: Option<String>) {}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+pub(crate) fn apply(attrs: winit::window::WindowAttributes, _platform: PlatformWindowAttributes, _display: &rwh_06::RawDisplayHandle) -> winit::window::WindowAttributes {
+    attrs
+}
@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+
+use napi::bindgen_prelude::*;
+
+use crate::window::Window;
+
+/// Bookkeeping for windows created through `ActiveEventLoop.createWindow`
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowRegistryOptions {
+    /// Close a window once its `CloseRequested` handler has run
+    pub close_on_request: Option<bool>,
+    /// Exit the event loop when the last tracked window is closed
+    pub exit_on_last_close: Option<bool>,
+}
+
+struct Entry {
+    id: winit::window::WindowId,
+    env: Env,
+    window: Reference<Window>,
+}
+
+#[derive(Default)]
+struct Registry {
+    entries: Vec<Entry>,
+    options: WindowRegistryOptions,
+    cleanup_hooked: bool,
+}
+
+// winit only allows one event loop per process, so one registry per js thread is enough
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+pub(crate) enum Closing {
+    None,
+    Requested,
+    Destroyed,
+}
+
+impl From<&winit::event::WindowEvent> for Closing {
+    fn from(event: &winit::event::WindowEvent) -> Self {
+        match event {
+            winit::event::WindowEvent::CloseRequested => Closing::Requested,
+            winit::event::WindowEvent::Destroyed => Closing::Destroyed,
+            _ => Closing::None,
+        }
+    }
+}
+
+/// Applies the registry options once the window event handler has returned
+pub(crate) struct AfterWindowEvent<'a> {
+    event_loop: &'a winit::event_loop::ActiveEventLoop,
+    id: winit::window::WindowId,
+    closing: Closing,
+}
+
+impl<'a> AfterWindowEvent<'a> {
+    pub(crate) fn new(event_loop: &'a winit::event_loop::ActiveEventLoop, id: winit::window::WindowId, event: &winit::event::WindowEvent) -> Self {
+        Self { event_loop, id, closing: Closing::from(event) }
+    }
+}
+
+impl Drop for AfterWindowEvent<'_> {
+    fn drop(&mut self) {
+        let close_on_request = REGISTRY.with_borrow(|registry| registry.options.close_on_request.unwrap_or(false));
+
+        match self.closing {
+            Closing::Requested if close_on_request => { close(self.event_loop, self.id); }
+            Closing::Destroyed => { close(self.event_loop, self.id); }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn set_options(options: WindowRegistryOptions) {
+    REGISTRY.with_borrow_mut(|registry| registry.options = options);
+}
+
+pub(crate) fn track(env: Env, window: Window) -> Result<Reference<Window>> {
+    let id = window.inner()?.id();
+    let window = Window::into_reference(window, env)?;
+    let tracked = window.clone(env)?;
+
+    let hook_cleanup = REGISTRY.with_borrow_mut(|registry| {
+        registry.entries.push(Entry { id, env, window: tracked });
+        !std::mem::replace(&mut registry.cleanup_hooked, true)
+    });
+
+    // release the references while the env is still alive
+    if hook_cleanup {
+        env.add_env_cleanup_hook((), |_| {
+            REGISTRY.with_borrow_mut(|registry| registry.entries.clear());
+        })?;
+    }
+
+    Ok(window)
+}
+
+pub(crate) fn get(id: winit::window::WindowId) -> Option<Reference<Window>> {
+    REGISTRY.with_borrow(|registry| {
+        registry.entries.iter()
+            .find(|entry| entry.id == id)
+            .and_then(|Entry { env, window, .. }| window.clone(*env).ok())
+    })
+}
+
+pub(crate) fn all() -> Vec<Reference<Window>> {
+    REGISTRY.with_borrow(|registry| {
+        registry.entries.iter()
+            .filter_map(|Entry { env, window, .. }| window.clone(*env).ok())
+            .collect()
+    })
+}
+
+pub(crate) fn count() -> u32 {
+    REGISTRY.with_borrow(|registry| registry.entries.len() as u32)
+}
+
+/// Stop tracking the window and drop the native window, js handles of it throw from now on.
+///
+/// winit destroys the window and sends `Destroyed` right away, unless a `BufferSurface` still draws to it
+pub(crate) fn close(event_loop: &winit::event_loop::ActiveEventLoop, id: winit::window::WindowId) -> bool {
+    let (removed, exit) = REGISTRY.with_borrow_mut(|registry| {
+        let Some(index) = registry.entries.iter().position(|entry| entry.id == id)
+        else { return (None, false) };
+
+        let removed = registry.entries.remove(index);
+        let exit = registry.entries.is_empty() && registry.options.exit_on_last_close.unwrap_or(false);
+        (Some(removed), exit)
+    });
+
+    let Some(Entry { mut window, .. }) = removed else { return false };
+    if let Some(inner) = window.release() {
+        inner.set_visible(false);
+    }
+    drop(window);
+
+    if exit {
+        event_loop.exit();
+    }
+    true
+}
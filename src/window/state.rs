@@ -0,0 +1,262 @@
+use napi::bindgen_prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dpi::{Position, Size},
+    event_loop::ActiveEventLoop,
+    napi_reason,
+    window::{Fullscreen, Window, WindowAttributes},
+};
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MonitorRect {
+    pub(crate) name: Option<String>,
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) scale_factor: f64,
+}
+
+impl From<&winit::monitor::MonitorHandle> for MonitorRect {
+    fn from(monitor: &winit::monitor::MonitorHandle) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            name: monitor.name(),
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+impl MonitorRect {
+    fn overlap(&self, x: i64, y: i64, width: i64, height: i64) -> i64 {
+        let w = (x + width).min(self.x as i64 + self.width as i64) - x.max(self.x as i64);
+        let h = (y + height).min(self.y as i64 + self.height as i64) - y.max(self.y as i64);
+        w.max(0) * h.max(0)
+    }
+}
+
+/// Outer position and inner size in physical pixels of the target monitor
+#[derive(PartialEq, Debug)]
+pub(crate) struct Placement {
+    pub(crate) position: Option<(i32, i32)>,
+    pub(crate) size: (u32, u32),
+}
+
+/// Find the monitor the window goes back to, rescale when its scale factor changed, then keep the window on it
+pub(crate) fn place(state: &WindowState, monitors: &[MonitorRect]) -> Placement {
+    let (width, height) = (state.width as i64, state.height as i64);
+    let (frame_width, frame_height) = (
+        (state.outer_width as i64 - width).max(0),
+        (state.outer_height as i64 - height).max(0),
+    );
+
+    let target = state.monitor.as_ref()
+        .and_then(|saved| monitors.iter().find(|monitor| saved.name.is_some() && monitor.name == saved.name))
+        .or_else(|| {
+            let (x, y) = state.position?;
+            monitors.iter()
+                .map(|monitor| (monitor, monitor.overlap(x as i64, y as i64, width + frame_width, height + frame_height)))
+                .filter(|(_, area)| *area > 0)
+                .max_by_key(|(_, area)| *area)
+                .map(|(monitor, _)| monitor)
+        })
+        .or(monitors.first());
+
+    let Some(target) = target else {
+        return Placement { position: state.position, size: (state.width, state.height) };
+    };
+
+    let ratio = target.scale_factor / state.scale_factor;
+    let scale = |value: i64| (value as f64 * ratio).round() as i64;
+    let (width, height) = (scale(width).max(1), scale(height).max(1));
+    let (frame_width, frame_height) = (scale(frame_width), scale(frame_height));
+
+    let (outer_width, outer_height) = (
+        (width + frame_width).min(target.width as i64),
+        (height + frame_height).min(target.height as i64),
+    );
+    let size = (
+        (outer_width - frame_width).max(1) as u32,
+        (outer_height - frame_height).max(1) as u32,
+    );
+
+    // keep the offset inside the monitor the window was on, on whichever monitor it lands
+    let position = state.position.map(|(x, y)| {
+        let (origin_x, origin_y) = match &state.monitor {
+            Some(saved) => (saved.x as i64, saved.y as i64),
+            None => (target.x as i64, target.y as i64),
+        };
+        let x = target.x as i64 + scale(x as i64 - origin_x);
+        let y = target.y as i64 + scale(y as i64 - origin_y);
+
+        let x = x.min(target.x as i64 + target.width as i64 - outer_width).max(target.x as i64);
+        let y = y.min(target.y as i64 + target.height as i64 - outer_height).max(target.y as i64);
+        (x as i32, y as i32)
+    });
+
+    Placement { position, size }
+}
+
+/// Geometry of a window, to reopen it where it was left
+#[napi]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    /// Outer position, `None` where windows can not be positioned (Wayland)
+    pub(crate) position: Option<(i32, i32)>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) outer_width: u32,
+    pub(crate) outer_height: u32,
+    pub(crate) scale_factor: f64,
+    pub(crate) maximized: bool,
+    pub(crate) fullscreen: bool,
+    pub(crate) monitor: Option<MonitorRect>,
+}
+
+#[napi]
+impl WindowState {
+    #[napi(factory)]
+    pub fn capture(window: &Window) -> Result<Self> {
+        let window = window.inner()?;
+        let inner_size = window.inner_size();
+        let outer_size = window.outer_size();
+
+        Ok(Self {
+            position: window.outer_position().ok().map(|position| (position.x, position.y)),
+            width: inner_size.width,
+            height: inner_size.height,
+            outer_width: outer_size.width,
+            outer_height: outer_size.height,
+            scale_factor: window.scale_factor(),
+            maximized: window.is_maximized(),
+            fullscreen: window.fullscreen().is_some(),
+            monitor: window.current_monitor().as_ref().map(MonitorRect::from),
+        })
+    }
+
+    #[napi(factory)]
+    pub fn from_json(json: String) -> Result<Self> {
+        serde_json::from_str(&json).map_err(|e| napi_reason!("invalid window state: {e}"))
+    }
+
+    #[napi]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| napi_reason!("{e}"))
+    }
+
+    #[napi(getter)]
+    pub fn position(&self) -> Option<Position> {
+        self.position.map(|(x, y)| Position::Physical { x: x as f64, y: y as f64 })
+    }
+
+    #[napi(getter)]
+    pub fn inner_size(&self) -> Size {
+        Size::Physical { width: self.width as f64, height: self.height as f64 }
+    }
+
+    #[napi(getter)]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    #[napi(getter)]
+    pub fn maximized(&self) -> bool {
+        self.maximized
+    }
+
+    #[napi(getter)]
+    pub fn fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    #[napi(getter)]
+    pub fn monitor_name(&self) -> Option<String> {
+        self.monitor.as_ref().and_then(|monitor| monitor.name.clone())
+    }
+
+    /// Copy of `attributes` (or the defaults) with the geometry fitted to the monitors available now
+    #[napi]
+    pub fn restore(&self, event_loop: &ActiveEventLoop, attributes: Option<&WindowAttributes>) -> Result<WindowAttributes> {
+        let event_loop = event_loop.inner()?;
+        let monitors = event_loop.available_monitors()
+            .map(|monitor| MonitorRect::from(&monitor))
+            .collect::<Vec<_>>();
+
+        let Placement { position, size: (width, height) } = place(self, &monitors);
+
+        let mut attributes = attributes.cloned().unwrap_or_default();
+        attributes.with_inner_size(Size::Physical { width: width as f64, height: height as f64 });
+        if let Some((x, y)) = position {
+            attributes.with_position(Position::Physical { x: x as f64, y: y as f64 });
+        }
+        attributes.with_maximized(self.maximized);
+        attributes.with_fullscreen(self.fullscreen.then_some(Fullscreen::Borderless));
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{place, MonitorRect, Placement, WindowState};
+
+    fn monitor(name: &str, x: i32, width: u32, scale_factor: f64) -> MonitorRect {
+        MonitorRect { name: Some(name.to_owned()), x, y: 0, width, height: 1080, scale_factor }
+    }
+
+    fn state(position: (i32, i32), size: (u32, u32), monitor: MonitorRect) -> WindowState {
+        WindowState {
+            position: Some(position),
+            width: size.0,
+            height: size.1,
+            outer_width: size.0,
+            outer_height: size.1 + 30,
+            scale_factor: monitor.scale_factor,
+            maximized: false,
+            fullscreen: false,
+            monitor: Some(monitor),
+        }
+    }
+
+    #[test]
+    fn test_same_monitor() {
+        let saved = state((100, 100), (800, 600), monitor("A", 0, 1920, 1.));
+        let placement = place(&saved, &[monitor("A", 0, 1920, 1.)]);
+        assert_eq!(placement, Placement { position: Some((100, 100)), size: (800, 600) });
+    }
+
+    #[test]
+    fn test_rescaled_monitor() {
+        let saved = state((100, 100), (800, 600), monitor("A", 0, 1920, 1.));
+        let placement = place(&saved, &[monitor("A", 0, 3840, 2.)]);
+        assert_eq!(placement, Placement { position: Some((200, 0)), size: (1600, 1020) });
+    }
+
+    #[test]
+    fn test_monitor_gone() {
+        let saved = state((2000, 100), (800, 600), monitor("B", 1920, 1920, 1.));
+        let placement = place(&saved, &[monitor("A", 0, 1920, 1.)]);
+        assert_eq!(placement, Placement { position: Some((80, 100)), size: (800, 600) });
+    }
+
+    #[test]
+    fn test_clamped_into_monitor() {
+        let saved = state((1800, 900), (800, 600), monitor("A", 0, 1920, 1.));
+        let placement = place(&saved, &[monitor("A", 0, 1920, 1.)]);
+        assert_eq!(placement, Placement { position: Some((1120, 450)), size: (800, 600) });
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let saved = state((10, 20), (640, 480), monitor("A", 0, 1920, 1.5));
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(serde_json::from_str::<WindowState>(&json).unwrap(), saved);
+    }
+}